@@ -13,14 +13,15 @@ pub fn update_all_cargo_tomls(target: &Utf8Path, files: &[RepoFile]) -> Result<(
     index.update()?;
 
     for file in files {
-        if file.name().file_name() == Some("Cargo.toml") {
-            let target_file = target.join(file.name());
+        let target_file = target.join(file.output());
+
+        if file.output().file_name() == Some("Cargo.toml") && target_file.exists() {
             let file_content = fs::read_to_string(&target_file)?;
             let mut doc = file_content.parse::<Document>()?;
 
             for table in ["dependencies", "dev-dependencies", "build-dependencies"] {
                 let updates = update_versions(&index, &mut doc, table);
-                print_updates(file.name(), updates);
+                print_updates(file.output(), updates);
             }
 
             fs::write(target_file, doc.to_string())?;
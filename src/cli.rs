@@ -8,6 +8,8 @@ use camino::{Utf8Path, Utf8PathBuf};
 use clap::{Args, CommandFactory, Parser, ValueHint};
 use clap_complete::Shell;
 
+use crate::merge::Strategy;
+
 #[derive(Parser)]
 #[command(name = "cargo", bin_name = "cargo")]
 enum Cli {
@@ -24,7 +26,11 @@ pub enum Command {
         name: Option<String>,
     },
     /// List all configured bookmarks with name and description.
-    List,
+    List {
+        /// List the pre-defined variables available in every template instead.
+        #[arg(long)]
+        builtins: bool,
+    },
     /// Create a new project from configured bookmarks.
     New {
         /// Bookmark as defined in the global configuration.
@@ -50,6 +56,15 @@ pub enum Command {
         #[command(flatten)]
         flags: CreationFlags,
     },
+    /// Remove the remembered answers from previous project generations.
+    Forget {
+        /// Bookmark, Git URL or local path of the template. Answers of all templates are removed
+        /// if omitted.
+        template: Option<String>,
+        /// An optional sub-folder within the repository that contains the template.
+        #[arg(long)]
+        folder: Option<Utf8PathBuf>,
+    },
     /// Generate auto-completion scripts for various shells.
     Completions {
         /// Shell to generate an auto-completion script for.
@@ -67,6 +82,7 @@ pub enum Command {
 }
 
 #[derive(Args)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct CreationFlags {
     /// Name of the new project, using the current working directory if omitted.
     ///
@@ -77,6 +93,44 @@ pub struct CreationFlags {
     /// Update all dependencies to the latest compatible version after project creation.
     #[arg(short, long)]
     pub update_deps: bool,
+    /// Apply the default values of a preset from the global configuration. Can be repeated, in
+    /// which case later presets take precedence over earlier ones.
+    #[arg(long = "preset", value_name = "NAME")]
+    pub presets: Vec<String>,
+    /// Don't pre-select the answers of the last run from the same template, and don't remember
+    /// the answers of this run.
+    #[arg(long)]
+    pub no_remember: bool,
+    /// Fail instead of only warning, if the bookmark contains defaults for settings that don't
+    /// exist in the template.
+    #[arg(long)]
+    pub strict: bool,
+    /// Replace files that already exist in the target directory.
+    #[arg(long, conflicts_with_all = ["skip_existing", "backup"])]
+    pub overwrite: bool,
+    /// Keep files that already exist in the target directory.
+    #[arg(long, conflicts_with = "backup")]
+    pub skip_existing: bool,
+    /// Replace files that already exist in the target directory, but keep a copy of the original
+    /// with a `.bak` extension.
+    #[arg(long)]
+    pub backup: bool,
+}
+
+impl CreationFlags {
+    /// How to handle files, that already exist in the target directory.
+    #[must_use]
+    pub fn merge_strategy(&self) -> Strategy {
+        if self.overwrite {
+            Strategy::Overwrite
+        } else if self.skip_existing {
+            Strategy::Skip
+        } else if self.backup {
+            Strategy::Backup
+        } else {
+            Strategy::Ask
+        }
+    }
 }
 
 #[must_use]
@@ -10,6 +10,8 @@
 pub mod cargo;
 pub mod cli;
 pub mod dirs;
+pub mod licenses;
+pub mod merge;
 pub mod repo;
 pub mod settings;
 pub mod templates;
@@ -0,0 +1,181 @@
+//! Embedded license texts, that are written into generated projects based on a SPDX license
+//! expression.
+//!
+//! The texts are Tera templates themselves, so copyright lines can be filled with values from the
+//! context, like the `year` and `git_name`.
+
+use std::fs;
+
+use anyhow::{Context, Result};
+use camino::{Utf8Path, Utf8PathBuf};
+use crossterm::style::Stylize;
+use tera::{Context as TeraContext, Tera};
+
+/// All license texts that are available for generation, keyed by their SPDX identifier. The
+/// `-only` and `-or-later` variants of the GNU licenses share the same text.
+static LICENSES: &[(&str, &str)] = &[
+    ("0BSD", include_str!("0BSD.txt")),
+    ("AGPL-3.0", include_str!("AGPL-3.0.txt")),
+    ("Apache-2.0", include_str!("Apache-2.0.txt")),
+    ("BSD-2-Clause", include_str!("BSD-2-Clause.txt")),
+    ("BSD-3-Clause", include_str!("BSD-3-Clause.txt")),
+    ("BSL-1.0", include_str!("BSL-1.0.txt")),
+    ("GPL-3.0", include_str!("GPL-3.0.txt")),
+    ("ISC", include_str!("ISC.txt")),
+    ("LGPL-3.0", include_str!("LGPL-3.0.txt")),
+    ("MIT", include_str!("MIT.txt")),
+    ("MPL-2.0", include_str!("MPL-2.0.txt")),
+    ("Unlicense", include_str!("Unlicense.txt")),
+    ("Zlib", include_str!("Zlib.txt")),
+];
+
+/// Write the license files for all licenses of the given SPDX expression into the target
+/// directory.
+///
+/// A single license is written to a `LICENSE` file. Multiple licenses, like the common
+/// `MIT OR Apache-2.0`, are written to separate files in the form of `LICENSE-<NAME>` instead.
+/// Files that already exist (for example, because the template provided its own version) are left
+/// untouched.
+///
+/// Returns the relative paths of all written files.
+pub fn write(
+    target: &Utf8Path,
+    expression: &str,
+    context: &TeraContext,
+) -> Result<Vec<Utf8PathBuf>> {
+    let ids = license_ids(expression)?;
+    let mut written = Vec::new();
+
+    for id in &ids {
+        let Some(text) = find_text(id) else {
+            eprintln!(
+                "{} no license text available for `{id}`, skipping license file",
+                "warning:".yellow().bold()
+            );
+            continue;
+        };
+
+        let name = Utf8PathBuf::from(if ids.len() == 1 {
+            "LICENSE".to_owned()
+        } else {
+            format!("LICENSE-{}", file_suffix(id))
+        });
+
+        let path = target.join(&name);
+        if path.exists() {
+            continue;
+        }
+
+        let rendered = Tera::one_off(text, context, false)
+            .with_context(|| format!("failed to render license text for `{id}`"))?;
+        fs::write(&path, rendered).with_context(|| format!("failed to write `{name}`"))?;
+
+        written.push(name);
+    }
+
+    Ok(written)
+}
+
+/// Extract all unique license identifiers from the expression, in order of appearance. License
+/// references like `LicenseRef-Custom` are included as well, although there is no text for them.
+fn license_ids(expression: &str) -> Result<Vec<String>> {
+    let expression = spdx::Expression::parse(expression)
+        .map_err(|e| anyhow::anyhow!("{}", e.reason))
+        .with_context(|| format!("invalid license expression `{expression}`"))?;
+
+    let mut ids = Vec::new();
+
+    for req in expression.requirements() {
+        let id = match req.req.license.id() {
+            Some(id) => id.name.to_owned(),
+            None => req.req.license.to_string(),
+        };
+
+        if !ids.contains(&id) {
+            ids.push(id);
+        }
+    }
+
+    Ok(ids)
+}
+
+fn find_text(id: &str) -> Option<&'static str> {
+    let id = normalize(id);
+    LICENSES
+        .iter()
+        .find_map(|(name, text)| (*name == id).then_some(*text))
+}
+
+/// Strip the `-only`, `-or-later` and `+` suffixes, that don't change the license text.
+fn normalize(id: &str) -> &str {
+    id.strip_suffix("-only")
+        .or_else(|| id.strip_suffix("-or-later"))
+        .or_else(|| id.strip_suffix('+'))
+        .unwrap_or(id)
+}
+
+/// Suffix for license files when multiple licenses are used. Apache is shortened, as it is common
+/// practice in the Rust ecosystem, to have a `LICENSE-APACHE` and `LICENSE-MIT` file.
+fn file_suffix(id: &str) -> &str {
+    match normalize(id) {
+        "Apache-2.0" => "APACHE",
+        id => id,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single_license() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = Utf8Path::from_path(dir.path()).unwrap();
+
+        let mut ctx = TeraContext::new();
+        ctx.insert("year", &2023);
+        ctx.insert("git_name", "Hatchling");
+
+        let written = write(target, "MIT", &ctx).unwrap();
+        assert_eq!(vec![Utf8PathBuf::from("LICENSE")], written);
+
+        let content = fs::read_to_string(target.join("LICENSE")).unwrap();
+        assert!(content.contains("Copyright (c) 2023 Hatchling"));
+    }
+
+    #[test]
+    fn dual_license() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = Utf8Path::from_path(dir.path()).unwrap();
+
+        let mut ctx = TeraContext::new();
+        ctx.insert("year", &2023);
+        ctx.insert("git_name", "Hatchling");
+
+        let written = write(target, "MIT OR Apache-2.0", &ctx).unwrap();
+        assert_eq!(
+            vec![
+                Utf8PathBuf::from("LICENSE-MIT"),
+                Utf8PathBuf::from("LICENSE-APACHE")
+            ],
+            written
+        );
+    }
+
+    #[test]
+    fn gnu_variants() {
+        assert!(find_text("GPL-3.0-only").is_some());
+        assert!(find_text("GPL-3.0-or-later").is_some());
+        assert!(find_text("AGPL-3.0-only").is_some());
+        assert!(find_text(&license_ids("GPL-3.0-or-later").unwrap()[0]).is_some());
+    }
+
+    #[test]
+    fn license_refs() {
+        assert_eq!(
+            vec!["MIT".to_owned(), "LicenseRef-Custom".to_owned()],
+            license_ids("MIT OR LicenseRef-Custom").unwrap()
+        );
+        assert!(find_text("LicenseRef-Custom").is_none());
+    }
+}
@@ -1,4 +1,4 @@
-use std::{collections::HashMap, convert::TryFrom, env, fs};
+use std::{collections::HashMap, convert::TryFrom, env, fs, io};
 
 use anyhow::{anyhow, bail, ensure, Context, Result};
 use camino::{Utf8Path, Utf8PathBuf};
@@ -6,10 +6,16 @@ use cargo_hatch::{
     cargo,
     cli::{self, Command, CreationFlags},
     dirs::Utf8ProjectDirs,
+    licenses,
+    merge::{Merger, Staging, Strategy},
     repo,
-    settings::{self, DefaultSetting},
+    settings::{
+        self, Answers, DefaultSetting, DefaultSource, GlobalSettings, RepoSettings, TemplateSource,
+        BUILTINS,
+    },
     templates,
 };
+use crossterm::{style::Stylize, tty::IsTty};
 use inquire::Confirm;
 
 fn main() -> Result<()> {
@@ -25,7 +31,23 @@ fn main() -> Result<()> {
 
             println!("TODO! init at {cwd}");
         }
-        Command::List => {
+        Command::List { builtins: true } => {
+            let width = BUILTINS
+                .iter()
+                .map(|builtin| builtin.name.len())
+                .max()
+                .unwrap_or_default();
+
+            for builtin in BUILTINS {
+                println!(
+                    "{:width$} - {} {}",
+                    builtin.name,
+                    builtin.description,
+                    format!("({})", builtin.ty).dark_grey(),
+                );
+            }
+        }
+        Command::List { builtins: false } => {
             let settings = settings::load_global(&dirs)?;
             let width = settings
                 .bookmarks
@@ -41,12 +63,15 @@ fn main() -> Result<()> {
                 );
             }
         }
-        Command::New { bookmark, flags } => {
+        Command::New {
+            bookmark: name,
+            flags,
+        } => {
             let mut settings = settings::load_global(&dirs)?;
             let bookmark = settings
                 .bookmarks
-                .remove(&bookmark)
-                .ok_or_else(|| anyhow!("bookmark with name `{bookmark}` unknown"))?;
+                .remove(&name)
+                .ok_or_else(|| anyhow!("bookmark with name `{name}` unknown"))?;
 
             let mut path = if bookmark.repository.starts_with("git@")
                 || bookmark.repository.starts_with("http:")
@@ -76,14 +101,28 @@ fn main() -> Result<()> {
                 );
             };
 
-            if let Some(folder) = &bookmark.folder {
+            let source = TemplateSource {
+                commit: repo::head_commit(&path),
+                source: bookmark.repository,
+                folder: bookmark.folder,
+            };
+
+            if let Some(folder) = &source.folder {
                 path.push(folder);
             }
 
-            generate_project(&path, flags, bookmark.defaults)?;
+            generate_project(
+                &dirs,
+                &path,
+                &source,
+                &settings,
+                flags,
+                Some((name, bookmark.defaults)),
+            )?;
             println!("done!");
         }
         Command::Git { folder, url, flags } => {
+            let settings = settings::load_global(&dirs)?;
             let mut path = {
                 let base = dirs.cache_dir();
                 let repo_name =
@@ -95,17 +134,56 @@ fn main() -> Result<()> {
 
             repo::clone_or_update(&url, &path).context("failed cloning")?;
 
-            if let Some(folder) = folder {
+            let source = TemplateSource {
+                commit: repo::head_commit(&path),
+                source: url,
+                folder,
+            };
+
+            if let Some(folder) = &source.folder {
                 path.push(folder);
             }
 
-            generate_project(&path, flags, HashMap::new())?;
+            generate_project(&dirs, &path, &source, &settings, flags, None)?;
             println!("done!");
         }
         Command::Local { path, flags } => {
-            generate_project(&path, flags, HashMap::new())?;
+            let settings = settings::load_global(&dirs)?;
+            let source = TemplateSource {
+                source: path.to_string(),
+                folder: None,
+                commit: repo::head_commit(&path),
+            };
+
+            generate_project(&dirs, &path, &source, &settings, flags, None)?;
             println!("done!");
         }
+        Command::Forget { template, folder } => {
+            let key = template.map(|template| {
+                let settings = settings::load_global(&dirs)?;
+                let source = match settings.bookmarks.get(&template) {
+                    Some(bookmark) => TemplateSource {
+                        source: bookmark.repository.clone(),
+                        folder: bookmark.folder.clone(),
+                        commit: None,
+                    },
+                    None => TemplateSource {
+                        source: template,
+                        folder,
+                        commit: None,
+                    },
+                };
+
+                anyhow::Ok(source.key())
+            });
+            let key = key.transpose()?;
+
+            if settings::clear_answers(&dirs, key.as_deref())? {
+                println!("done!");
+            } else {
+                println!("no remembered answers found");
+            }
+        }
         Command::Completions { shell } => cli::completions(shell),
         Command::Manpages { dir } => cli::manpages(&dir)?,
     }
@@ -114,34 +192,143 @@ fn main() -> Result<()> {
 }
 
 fn generate_project(
+    dirs: &Utf8ProjectDirs,
     path: &Utf8Path,
+    source: &TemplateSource,
+    global: &GlobalSettings,
     flags: CreationFlags,
-    defaults: HashMap<String, DefaultSetting>,
+    bookmark: Option<(String, HashMap<String, DefaultSetting>)>,
 ) -> Result<()> {
-    let (name, target) = get_target_dir(flags.name).context("failed preparing target directory")?;
+    let strategy = flags.merge_strategy();
+    let (name, target) =
+        get_target_dir(flags.name, strategy).context("failed preparing target directory")?;
 
-    let files = templates::collect_files(path).context("failed collecting files")?;
     let repo_settings = settings::load_repo(path).context("failed loading hatch config")?;
+    let files =
+        templates::collect_files(path, &repo_settings).context("failed collecting files")?;
+
+    let author = {
+        let names = ["git_author", "git_name", "git_email"];
+        let required = repo_settings.references(&names) || templates::references(&files, &names)?;
+        settings::find_author(&global.author, required).context("failed finding author")?
+    };
+
+    let mut sources = Vec::new();
+
+    if let Some((name, defaults)) = &bookmark {
+        sources.push((DefaultSource::Bookmark(name), defaults));
+    }
 
-    let mut context =
-        settings::new_context(&repo_settings, &name).context("failed creating context")?;
-    settings::fill_context(&mut context, repo_settings.args, defaults)
+    for name in &flags.presets {
+        let preset = global
+            .presets
+            .get(name)
+            .ok_or_else(|| anyhow!("preset with name `{name}` unknown"))?;
+
+        sources.push((DefaultSource::Preset(name), preset));
+    }
+
+    let key = source.key();
+    let remembered = if flags.no_remember {
+        Answers::default()
+    } else {
+        settings::load_answers(dirs, &key).context("failed loading answers")?
+    };
+    let defaults = repo_settings.merge_defaults(&sources, remembered)?;
+
+    let mut context = settings::new_context(&repo_settings, &name, source, &author)
+        .context("failed creating context")?;
+    let unused = settings::fill_context(&mut context, &repo_settings, defaults)
         .context("failed filling context")?;
 
-    let files = templates::filter_ignored(files, &context, repo_settings.ignore)?;
-    templates::render(&files, &context, &target).context("failed rendering templates")?;
+    for (source, defaults) in &sources {
+        let unused = defaults
+            .keys()
+            .filter(|name| unused.contains_key(*name))
+            .collect::<Vec<_>>();
+        // Presets are shared between templates, so their leftovers are never treated as errors.
+        let strict = flags.strict && matches!(source, DefaultSource::Bookmark(_));
+        report_unused(*source, &unused, &repo_settings, strict)?;
+    }
+
+    settings::validate_context(&mut context, &repo_settings)
+        .context("failed validating context")?;
+    settings::review_context(&mut context, &repo_settings).context("failed reviewing context")?;
+
+    let licenses = repo_settings.licenses(&context);
+    let answers = repo_settings.answers(&context);
+    let files = templates::filter_ignored(
+        files,
+        &context,
+        repo_settings.ignore,
+        &repo_settings.foreach,
+    )?;
+    let staging = Staging::new(&target)?;
+
+    templates::render(&files, &context, staging.path(), repo_settings.skip_empty)
+        .context("failed rendering templates")?;
+    templates::apply_permissions(&files, staging.path(), &repo_settings.permissions)
+        .context("failed applying file permissions")?;
+
+    for license in licenses {
+        licenses::write(staging.path(), &license, &context)
+            .context("failed writing license files")?;
+    }
 
     if flags.update_deps {
-        cargo::update_all_cargo_tomls(&target, &files)?;
+        cargo::update_all_cargo_tomls(staging.path(), &files)?;
     }
 
-    repo::init(&target).context("failed initializing git repository")?;
+    if !target.join(".git").exists() {
+        repo::init(staging.path()).context("failed initializing git repository")?;
+    }
+
+    Merger::new(strategy)
+        .apply(staging.path(), &target)
+        .context("failed moving project into place")?;
+
+    if !flags.no_remember {
+        settings::save_answers(dirs, &key, answers).context("failed remembering answers")?;
+    }
+
+    Ok(())
+}
+
+/// Report defaults of a bookmark or preset, that don't match any setting of the template, like
+/// typos or settings that were renamed in the template. They're printed as warnings, or returned
+/// as error in strict mode.
+fn report_unused(
+    source: DefaultSource<'_>,
+    unused: &[&String],
+    settings: &RepoSettings,
+    strict: bool,
+) -> Result<()> {
+    let messages = unused
+        .iter()
+        .map(|name| {
+            let message =
+                format!("default `{name}` of {source} matches no setting of the template");
+
+            match settings.suggest(name) {
+                Some(suggestion) => format!("{message} (did you mean `{suggestion}`?)"),
+                None => message,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    if strict && !messages.is_empty() {
+        bail!("{}", messages.join("\n"));
+    }
+
+    for message in messages {
+        eprintln!("{} {message}", "warning:".yellow().bold());
+    }
 
     Ok(())
 }
 
 /// Locate the target directory, and ensure it is suitable for project generation.
-fn get_target_dir(name: Option<Utf8PathBuf>) -> Result<(String, Utf8PathBuf)> {
+fn get_target_dir(name: Option<Utf8PathBuf>, strategy: Strategy) -> Result<(String, Utf8PathBuf)> {
     let out = env::current_dir().context("failed getting current directory")?;
     let mut out = Utf8PathBuf::try_from(out).context("current directory is not valid UTF-8")?;
 
@@ -159,14 +346,13 @@ fn get_target_dir(name: Option<Utf8PathBuf>) -> Result<(String, Utf8PathBuf)> {
         "target diretory appears to be an existing file"
     );
 
-    if out.exists() && !is_dir_empty(&out)? {
+    if strategy == Strategy::Ask && out.exists() && !is_dir_empty(&out)? && io::stdin().is_tty() {
         let mut prompt = Confirm::new("target directory already exists. Do you want to continue?");
         prompt.default = Some(false);
-        prompt.help_message = Some("if you continue, the directory will be cleared beforehand");
+        prompt.help_message =
+            Some("files are merged into the directory, asking before replacing any existing file");
 
-        if prompt.prompt().context("failed to prompt for user input")? {
-            fs::remove_dir_all(&out).context("failed clearing output directory")?;
-        } else {
+        if !prompt.prompt().context("failed to prompt for user input")? {
             bail!("generation cancelled by user");
         }
     }
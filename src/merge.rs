@@ -0,0 +1,418 @@
+//! Moving a generated project into its target directory.
+//!
+//! Projects are first generated into a [`Staging`] directory, and only moved into the target once
+//! all steps succeeded. If the target already exists, the files are merged into it, but existing
+//! files are never replaced without an explicit choice, either made upfront through a
+//! [`Strategy`] or interactively for each file.
+
+use std::{
+    fmt::{self, Display},
+    fs, io,
+};
+
+use anyhow::{bail, Context, Result};
+use camino::{Utf8Path, Utf8PathBuf};
+use crossterm::{style::Stylize, tty::IsTty};
+use inquire::Select;
+use tempfile::TempDir;
+
+/// How to handle files, that already exist in the target directory with different content.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Strategy {
+    /// Ask the user for each file.
+    Ask,
+    /// Replace the existing file.
+    Overwrite,
+    /// Keep the existing file.
+    Skip,
+    /// Replace the existing file, but keep a copy of it with a `.bak` extension.
+    Backup,
+}
+
+/// A temporary directory next to the target directory, that the project is generated into before
+/// it's moved into place. That way, the target is left untouched if any step of the generation
+/// fails.
+///
+/// The directory is removed once this value is dropped.
+pub struct Staging {
+    _dir: TempDir,
+    path: Utf8PathBuf,
+}
+
+impl Staging {
+    /// Create a new staging directory in the parent of the target directory, so the files can be
+    /// moved efficiently afterwards.
+    pub fn new(target: &Utf8Path) -> Result<Self> {
+        let parent = target
+            .parent()
+            .context("target directory has no parent directory")?;
+        fs::create_dir_all(parent).context("failed creating parent directory")?;
+
+        let dir = tempfile::Builder::new()
+            .prefix(".hatch-")
+            .tempdir_in(parent)
+            .context("failed creating staging directory")?;
+
+        // Use a sub-directory, as the temporary directory itself has restricted permissions.
+        let path = Utf8Path::from_path(dir.path())
+            .context("staging directory is not valid UTF-8")?
+            .join("project");
+        fs::create_dir(&path).context("failed creating staging directory")?;
+
+        Ok(Self { _dir: dir, path })
+    }
+
+    #[must_use]
+    pub fn path(&self) -> &Utf8Path {
+        &self.path
+    }
+}
+
+/// Moves the generated files from the staging directory into the target directory, while
+/// deciding for every existing file whether it is replaced.
+pub struct Merger {
+    strategy: Strategy,
+}
+
+/// Action to take for a single file or directory of the staging directory.
+enum Action {
+    /// Move it into the target, as nothing exists there yet or the existing file is replaced.
+    Move,
+    /// Move it into the target, after renaming the existing file to a backup.
+    Backup,
+}
+
+impl Merger {
+    #[must_use]
+    pub fn new(strategy: Strategy) -> Self {
+        Self { strategy }
+    }
+
+    /// Move all files from the staging directory into the target directory.
+    ///
+    /// The handling of every existing file is decided before anything is moved, so the target
+    /// is left untouched if the user aborts. Existing files with the same content are kept as
+    /// they are, and unrelated files are never touched.
+    pub fn apply(&mut self, staging: &Utf8Path, target: &Utf8Path) -> Result<()> {
+        if !target.exists() {
+            return fs::rename(staging, target).context("failed moving project into place");
+        }
+
+        let mut actions = Vec::new();
+        self.plan(staging, target, Utf8Path::new(""), &mut actions)?;
+
+        for (name, action) in actions {
+            let path = target.join(&name);
+
+            if let Action::Backup = action {
+                let backup = backup_path(&path);
+                fs::rename(&path, &backup)
+                    .with_context(|| format!("failed to back up `{name}` as `{backup}`"))?;
+            }
+
+            fs::rename(staging.join(&name), &path)
+                .with_context(|| format!("failed moving `{name}` into place"))?;
+        }
+
+        Ok(())
+    }
+
+    /// Decide the action for all entries of the given directory, recursively descending into
+    /// directories that exist in both locations.
+    fn plan(
+        &mut self,
+        staging: &Utf8Path,
+        target: &Utf8Path,
+        dir: &Utf8Path,
+        actions: &mut Vec<(Utf8PathBuf, Action)>,
+    ) -> Result<()> {
+        let entries = staging
+            .join(dir)
+            .read_dir_utf8()
+            .with_context(|| format!("failed listing staged files in `{dir}`"))?;
+
+        for entry in entries {
+            let entry = entry?;
+            let name = dir.join(entry.file_name());
+            let existing = target.join(&name);
+
+            let Ok(meta) = existing.symlink_metadata() else {
+                actions.push((name, Action::Move));
+                continue;
+            };
+
+            if entry.file_type()?.is_dir() {
+                if !meta.is_dir() {
+                    bail!("`{name}` already exists in the target, but isn't a directory");
+                }
+
+                self.plan(staging, target, &name, actions)?;
+                continue;
+            }
+
+            if meta.is_dir() {
+                bail!("`{name}` already exists in the target as directory");
+            }
+
+            let new = read(entry.path()).with_context(|| format!("failed reading `{name}`"))?;
+            let old =
+                read(&existing).with_context(|| format!("failed reading existing `{name}`"))?;
+
+            if old == new {
+                continue;
+            }
+
+            match self.resolve(&name, &old, &new)? {
+                Strategy::Ask | Strategy::Overwrite => actions.push((name, Action::Move)),
+                Strategy::Backup => actions.push((name, Action::Backup)),
+                Strategy::Skip => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Decide what to do with an existing file, asking the user if no strategy was set upfront.
+    fn resolve(&mut self, name: &Utf8Path, existing: &[u8], new: &[u8]) -> Result<Strategy> {
+        if self.strategy != Strategy::Ask {
+            return Ok(self.strategy);
+        }
+
+        if !io::stdin().is_tty() {
+            bail!(
+                "the file `{name}` already exists, use `--overwrite`, `--skip-existing` or \
+                 `--backup` to decide how to handle existing files"
+            );
+        }
+
+        loop {
+            let choice = Select::new(
+                &format!("the file `{name}` already exists, what do you want to do?"),
+                Choice::ALL.to_vec(),
+            )
+            .prompt()?;
+
+            return Ok(match choice {
+                Choice::Overwrite => Strategy::Overwrite,
+                Choice::Skip => Strategy::Skip,
+                Choice::Backup => Strategy::Backup,
+                Choice::Diff => {
+                    print_diff(existing, new);
+                    continue;
+                }
+                Choice::OverwriteAll => {
+                    self.strategy = Strategy::Overwrite;
+                    Strategy::Overwrite
+                }
+                Choice::SkipAll => {
+                    self.strategy = Strategy::Skip;
+                    Strategy::Skip
+                }
+            });
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Choice {
+    Overwrite,
+    Skip,
+    Backup,
+    Diff,
+    OverwriteAll,
+    SkipAll,
+}
+
+impl Choice {
+    const ALL: [Self; 6] = [
+        Self::Overwrite,
+        Self::Skip,
+        Self::Backup,
+        Self::Diff,
+        Self::OverwriteAll,
+        Self::SkipAll,
+    ];
+}
+
+impl Display for Choice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Overwrite => "overwrite",
+            Self::Skip => "skip",
+            Self::Backup => "overwrite, but keep a backup",
+            Self::Diff => "show diff",
+            Self::OverwriteAll => "overwrite this and all following files",
+            Self::SkipAll => "skip this and all following files",
+        })
+    }
+}
+
+/// Read the content of a file for comparison. Symlinks are not followed, but described by their
+/// link target instead, as they may point to directories or not-yet-existing files.
+fn read(path: &Utf8Path) -> io::Result<Vec<u8>> {
+    if path.is_symlink() {
+        let link = path.read_link_utf8()?;
+        Ok(format!("symlink to {link}").into_bytes())
+    } else {
+        fs::read(path)
+    }
+}
+
+/// Find a free path for the backup of a file, by appending `.bak` and a counter if needed.
+fn backup_path(path: &Utf8Path) -> Utf8PathBuf {
+    let mut backup = Utf8PathBuf::from(format!("{path}.bak"));
+    let mut counter = 1;
+
+    while backup.symlink_metadata().is_ok() {
+        backup = Utf8PathBuf::from(format!("{path}.bak.{counter}"));
+        counter += 1;
+    }
+
+    backup
+}
+
+/// A single line of a diff between two files.
+#[derive(Debug, PartialEq, Eq)]
+enum DiffLine<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// Print the line differences between the existing and new content of a file.
+fn print_diff(existing: &[u8], new: &[u8]) {
+    let (Ok(existing), Ok(new)) = (std::str::from_utf8(existing), std::str::from_utf8(new)) else {
+        println!("binary files differ");
+        return;
+    };
+
+    let Some(lines) = diff_lines(existing, new) else {
+        println!("files are too large to show a diff");
+        return;
+    };
+
+    for line in lines {
+        match line {
+            DiffLine::Same(line) => println!("  {line}"),
+            DiffLine::Removed(line) => println!("{}", format!("- {line}").red()),
+            DiffLine::Added(line) => println!("{}", format!("+ {line}").green()),
+        }
+    }
+}
+
+/// Calculate the line differences of two texts, based on their longest common subsequence.
+///
+/// Returns `None` if the texts are too large, to keep memory usage in check.
+fn diff_lines<'a>(old: &'a str, new: &'a str) -> Option<Vec<DiffLine<'a>>> {
+    const MAX_CELLS: usize = 4_000_000;
+
+    let old = old.lines().collect::<Vec<_>>();
+    let new = new.lines().collect::<Vec<_>>();
+
+    if old.len().saturating_mul(new.len()) > MAX_CELLS {
+        return None;
+    }
+
+    // Length of the longest common subsequence for all suffixes of both texts.
+    let mut lengths = vec![vec![0_usize; new.len() + 1]; old.len() + 1];
+
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i][j] = if old[i] == new[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut lines = Vec::with_capacity(old.len().max(new.len()));
+
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            lines.push(DiffLine::Same(old[i]));
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            lines.push(DiffLine::Removed(old[i]));
+            i += 1;
+        } else {
+            lines.push(DiffLine::Added(new[j]));
+            j += 1;
+        }
+    }
+
+    lines.extend(old[i..].iter().map(|line| DiffLine::Removed(line)));
+    lines.extend(new[j..].iter().map(|line| DiffLine::Added(line)));
+
+    Some(lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_strategies() {
+        let merge = |strategy| {
+            let dir = tempfile::tempdir().unwrap();
+            let root = Utf8Path::from_path(dir.path()).unwrap();
+            let (staging, target) = (root.join("staging"), root.join("target"));
+
+            fs::create_dir_all(staging.join("sub")).unwrap();
+            fs::write(staging.join("a.txt"), "new").unwrap();
+            fs::write(staging.join("b.txt"), "same").unwrap();
+            fs::write(staging.join("sub/c.txt"), "new").unwrap();
+
+            fs::create_dir_all(target.join("sub")).unwrap();
+            fs::write(target.join("a.txt"), "old").unwrap();
+            fs::write(target.join("b.txt"), "same").unwrap();
+            fs::write(target.join("sub/d.txt"), "unrelated").unwrap();
+
+            let result = Merger::new(strategy).apply(&staging, &target);
+            let read = |name: &str| fs::read_to_string(target.join(name)).ok();
+
+            (
+                result.is_ok(),
+                [
+                    read("a.txt"),
+                    read("a.txt.bak"),
+                    read("sub/c.txt"),
+                    read("sub/d.txt"),
+                ],
+            )
+        };
+
+        let new = || Some("new".to_owned());
+        let old = || Some("old".to_owned());
+        let unrelated = || Some("unrelated".to_owned());
+
+        assert_eq!(
+            (true, [old(), None, new(), unrelated()]),
+            merge(Strategy::Skip)
+        );
+        assert_eq!(
+            (true, [new(), None, new(), unrelated()]),
+            merge(Strategy::Overwrite)
+        );
+        assert_eq!(
+            (true, [new(), old(), new(), unrelated()]),
+            merge(Strategy::Backup)
+        );
+    }
+
+    #[test]
+    fn diff() {
+        assert_eq!(
+            Some(vec![
+                DiffLine::Same("a"),
+                DiffLine::Removed("b"),
+                DiffLine::Added("x"),
+                DiffLine::Same("c"),
+                DiffLine::Added("d"),
+            ]),
+            diff_lines("a\nb\nc", "a\nx\nc\nd")
+        );
+    }
+}
@@ -109,6 +109,15 @@ pub fn init(target: &Utf8Path) -> Result<()> {
     Ok(())
 }
 
+/// Get the commit hash of the current head, if the given path is located within a Git repository.
+#[must_use]
+pub fn head_commit(path: &Utf8Path) -> Option<String> {
+    let repo = Repository::discover(path).ok()?;
+    let commit = repo.head().ok()?.peel_to_commit().ok()?;
+
+    Some(commit.id().to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
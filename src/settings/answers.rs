@@ -0,0 +1,71 @@
+//! Storage for the answers of previous runs, so they can be pre-selected the next time a project
+//! is generated from the same template.
+
+use std::{collections::HashMap, fs, io::ErrorKind};
+
+use anyhow::{Context, Result};
+use camino::Utf8PathBuf;
+use indexmap::IndexMap;
+use tera::Value;
+
+use crate::dirs::Utf8ProjectDirs;
+
+/// Answers of a single template, keyed by setting name.
+pub type Answers = IndexMap<String, Value>;
+
+/// Load the remembered answers for the template with the given key. If no answers were stored
+/// yet, an empty list is returned.
+pub fn load(dirs: &Utf8ProjectDirs, key: &str) -> Result<Answers> {
+    Ok(load_all(dirs)?.remove(key).unwrap_or_default())
+}
+
+/// Store the answers for the template with the given key, replacing any previous ones.
+pub fn save(dirs: &Utf8ProjectDirs, key: &str, answers: Answers) -> Result<()> {
+    let mut all = load_all(dirs)?;
+    all.insert(key.to_owned(), answers);
+
+    save_all(dirs, &all)
+}
+
+/// Remove the remembered answers for the template with the given key, or all remembered answers
+/// if no key is given.
+///
+/// Returns whether any answers were removed.
+pub fn clear(dirs: &Utf8ProjectDirs, key: Option<&str>) -> Result<bool> {
+    let mut all = load_all(dirs)?;
+
+    let removed = if let Some(key) = key {
+        all.remove(key).is_some()
+    } else {
+        let removed = !all.is_empty();
+        all.clear();
+        removed
+    };
+
+    if removed {
+        save_all(dirs, &all)?;
+    }
+
+    Ok(removed)
+}
+
+fn file(dirs: &Utf8ProjectDirs) -> Utf8PathBuf {
+    dirs.cache_dir().join("answers.json")
+}
+
+fn load_all(dirs: &Utf8ProjectDirs) -> Result<HashMap<String, Answers>> {
+    let buf = match fs::read(file(dirs)) {
+        Ok(buf) => buf,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(HashMap::new()),
+        Err(e) => return Err(e).context("failed reading remembered answers"),
+    };
+
+    serde_json::from_slice(&buf).context("invalid remembered answers")
+}
+
+fn save_all(dirs: &Utf8ProjectDirs, all: &HashMap<String, Answers>) -> Result<()> {
+    let buf = serde_json::to_vec_pretty(all).context("failed serializing answers")?;
+
+    fs::create_dir_all(dirs.cache_dir()).context("failed creating cache directory")?;
+    fs::write(file(dirs), buf).context("failed writing remembered answers")
+}
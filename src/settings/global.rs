@@ -1,18 +1,27 @@
-use std::collections::{BTreeMap, HashMap, HashSet};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    io::ErrorKind,
+};
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use camino::Utf8PathBuf;
 use serde::Deserialize;
 
 use crate::dirs::Utf8ProjectDirs;
 
-#[derive(Deserialize)]
+#[derive(Default, Deserialize)]
 #[cfg_attr(test, derive(Debug, PartialEq))]
 pub struct Settings {
     #[serde(default)]
     pub git: Git,
     #[serde(default)]
+    pub author: Author,
+    #[serde(default)]
     pub bookmarks: BTreeMap<String, Bookmark>,
+    /// Named sets of default values, that can be applied to any template.
+    #[serde(default)]
+    pub presets: BTreeMap<String, HashMap<String, DefaultSetting>>,
     #[serde(default)]
     pub update_deps: bool,
 }
@@ -23,6 +32,15 @@ pub struct Git {
     pub ssh_key: Option<Utf8PathBuf>,
 }
 
+/// Author information, used for the `git_author`, `git_name` and `git_email` variables in
+/// templates.
+#[derive(Clone, Default, Deserialize)]
+#[cfg_attr(test, derive(Debug, Eq, PartialEq))]
+pub struct Author {
+    pub name: Option<String>,
+    pub email: Option<String>,
+}
+
 #[derive(Deserialize)]
 #[cfg_attr(test, derive(Debug, PartialEq))]
 pub struct Bookmark {
@@ -33,28 +51,108 @@ pub struct Bookmark {
     pub defaults: HashMap<String, DefaultSetting>,
 }
 
-#[derive(Deserialize)]
+/// Override for the default value of a single setting. It can be given as plain value, or as
+/// table with the `value` and further options.
+#[derive(Clone, Deserialize)]
 #[cfg_attr(test, derive(Debug, PartialEq))]
+#[serde(from = "RawDefaultSetting")]
 pub struct DefaultSetting {
     pub value: DefaultValue,
     pub skip_prompt: bool,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawDefaultSetting {
+    Full {
+        value: DefaultValue,
+        #[serde(default)]
+        skip_prompt: bool,
+    },
+    Plain(DefaultValue),
+}
+
+impl From<RawDefaultSetting> for DefaultSetting {
+    fn from(raw: RawDefaultSetting) -> Self {
+        match raw {
+            RawDefaultSetting::Full { value, skip_prompt } => Self { value, skip_prompt },
+            RawDefaultSetting::Plain(value) => Self {
+                value,
+                skip_prompt: false,
+            },
+        }
+    }
+}
+
+/// A plain default value, that is only matched against the type of a setting once it's applied.
+/// That way, a string can for example be used for string, list and license settings alike.
+#[derive(Clone, Debug, Deserialize)]
 #[cfg_attr(test, derive(PartialEq))]
-#[serde(rename_all = "snake_case")]
+#[serde(try_from = "RawDefaultValue")]
 pub enum DefaultValue {
     Bool(bool),
+    Number(i64),
+    Float(f64),
     String(String),
+    Array(Vec<String>),
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawDefaultValue {
+    Bool(bool),
     Number(i64),
     Float(f64),
-    List(String),
-    MultiList(HashSet<String>),
+    String(String),
+    Array(Vec<String>),
+    Typed(TypedValue),
+}
+
+/// Older, explicitly typed form of a default value, like `{ bool = true }`.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TypedValue {
+    bool: Option<bool>,
+    string: Option<String>,
+    number: Option<i64>,
+    float: Option<f64>,
+    list: Option<String>,
+    multi_list: Option<Vec<String>>,
+}
+
+impl TryFrom<RawDefaultValue> for DefaultValue {
+    type Error = &'static str;
+
+    fn try_from(raw: RawDefaultValue) -> Result<Self, Self::Error> {
+        Ok(match raw {
+            RawDefaultValue::Bool(value) => Self::Bool(value),
+            RawDefaultValue::Number(value) => Self::Number(value),
+            RawDefaultValue::Float(value) => Self::Float(value),
+            RawDefaultValue::String(value) => Self::String(value),
+            RawDefaultValue::Array(value) => Self::Array(value),
+            RawDefaultValue::Typed(typed) => typed
+                .bool
+                .map(Self::Bool)
+                .or_else(|| typed.string.map(Self::String))
+                .or_else(|| typed.number.map(Self::Number))
+                .or_else(|| typed.float.map(Self::Float))
+                .or_else(|| typed.list.map(Self::String))
+                .or_else(|| typed.multi_list.map(Self::Array))
+                .ok_or("typed default value is missing its value")?,
+        })
+    }
 }
 
+/// Load the global settings. If the settings file doesn't exist, the default settings are
+/// returned instead.
 pub fn load(dirs: &Utf8ProjectDirs) -> Result<Settings> {
-    let buf = std::fs::read(dirs.config_dir().join("settings.toml"))?;
-    basic_toml::from_slice(&buf).map_err(Into::into)
+    let buf = match fs::read(dirs.config_dir().join("settings.toml")) {
+        Ok(buf) => buf,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Settings::default()),
+        Err(e) => return Err(e).context("failed reading global settings file"),
+    };
+
+    basic_toml::from_slice(&buf).context("invalid global settings")
 }
 
 #[cfg(test)]
@@ -69,6 +167,10 @@ mod tests {
         [git]
         ssh_key = ".ssh/id_ed25519"
 
+        [author]
+        name = "Hatchling"
+        email = "hatchling@example.com"
+
         [bookmarks.server]
         repository = "test"
         description = "sample"
@@ -86,6 +188,10 @@ mod tests {
             git: Git {
                 ssh_key: Some(Utf8PathBuf::from(".ssh/id_ed25519")),
             },
+            author: Author {
+                name: Some("Hatchling".to_owned()),
+                email: Some("hatchling@example.com".to_owned()),
+            },
             bookmarks: [(
                 "server".to_owned(),
                 Bookmark {
@@ -124,16 +230,17 @@ mod tests {
                         (
                             "test_list".to_owned(),
                             DefaultSetting {
-                                value: DefaultValue::List("one".to_owned()),
+                                value: DefaultValue::String("one".to_owned()),
                                 skip_prompt: true,
                             },
                         ),
                         (
                             "test_multi_list".to_owned(),
                             DefaultSetting {
-                                value: DefaultValue::MultiList(
-                                    ["one".to_owned(), "two".to_owned()].into_iter().collect(),
-                                ),
+                                value: DefaultValue::Array(vec![
+                                    "one".to_owned(),
+                                    "two".to_owned(),
+                                ]),
                                 skip_prompt: true,
                             },
                         ),
@@ -144,10 +251,44 @@ mod tests {
             )]
             .into_iter()
             .collect(),
+            presets: BTreeMap::new(),
             update_deps: true,
         };
 
         let result = basic_toml::from_str::<Settings>(raw);
         assert_eq!(expect, result.unwrap());
     }
+
+    #[test]
+    fn plain_defaults() {
+        let raw = r#"
+        [presets.oss]
+        test_bool = true
+        test_string = "value"
+        test_number = 10
+        test_float = { value = 2.5, skip_prompt = true }
+        test_array = ["one", "two"]
+        "#;
+        let expect = [
+            ("test_bool", DefaultValue::Bool(true), false),
+            (
+                "test_string",
+                DefaultValue::String("value".to_owned()),
+                false,
+            ),
+            ("test_number", DefaultValue::Number(10), false),
+            ("test_float", DefaultValue::Float(2.5), true),
+            (
+                "test_array",
+                DefaultValue::Array(vec!["one".to_owned(), "two".to_owned()]),
+                false,
+            ),
+        ]
+        .into_iter()
+        .map(|(name, value, skip_prompt)| (name.to_owned(), DefaultSetting { value, skip_prompt }))
+        .collect::<HashMap<_, _>>();
+
+        let mut result = basic_toml::from_str::<Settings>(raw).unwrap();
+        assert_eq!(Some(expect), result.presets.remove("oss"));
+    }
 }
@@ -1,7 +1,14 @@
 pub use self::{
-    global::{load as load_global, DefaultSetting, Settings as GlobalSettings},
-    repo::{fill_context, load as load_repo, new_context, IgnorePattern, RepoSettings},
+    answers::{clear as clear_answers, load as load_answers, save as save_answers, Answers},
+    global::{load as load_global, Author, DefaultSetting, Settings as GlobalSettings},
+    repo::{
+        fill_context, find_author, load as load_repo, new_context, references, review_context,
+        validate_context, Builtin, ComputedSetting, ComputedType, DefaultSource, Delimiters,
+        FileMode, ForeachRule, IgnorePattern, RepoSettings, TemplateSource, ValidationRule,
+        BUILTINS,
+    },
 };
 
+mod answers;
 mod global;
 mod repo;
@@ -0,0 +1,77 @@
+//! Lookup of the author information from various sources, with a fallback to prompting the user.
+
+use std::env;
+
+use anyhow::{bail, Result};
+use git2::Config as GitConfig;
+use inquire::Text;
+
+use super::{prompts, validators};
+use crate::settings::global::Author;
+
+/// Find the author's name and email, by checking the following sources in order:
+///
+/// 1. The `[author]` section of the global settings.
+/// 2. The `CARGO_NAME`/`CARGO_EMAIL` and `GIT_AUTHOR_NAME`/`GIT_AUTHOR_EMAIL` environment
+///    variables, same as `cargo` does.
+/// 3. The `user.name` and `user.email` of the default git config.
+///
+/// If `required` is set and any of the values couldn't be found, the user is prompted for it. In
+/// case no interactive terminal is available, an error is returned instead.
+pub fn find(config: &Author, required: bool) -> Result<Author> {
+    let git = GitConfig::open_default()
+        .and_then(|mut config| config.snapshot())
+        .ok();
+    // Empty values count as missing, so the next source is checked instead.
+    let non_empty = |value: String| (!value.is_empty()).then_some(value);
+    let lookup = |field: &Option<String>, vars: [&str; 2], key: &str| {
+        field
+            .clone()
+            .and_then(non_empty)
+            .or_else(|| {
+                vars.into_iter()
+                    .find_map(|var| env::var(var).ok().and_then(non_empty))
+            })
+            .or_else(|| {
+                git.as_ref()
+                    .and_then(|git| git.get_string(key).ok())
+                    .and_then(non_empty)
+            })
+    };
+
+    let mut author = Author {
+        name: lookup(&config.name, ["CARGO_NAME", "GIT_AUTHOR_NAME"], "user.name"),
+        email: lookup(
+            &config.email,
+            ["CARGO_EMAIL", "GIT_AUTHOR_EMAIL"],
+            "user.email",
+        ),
+    };
+
+    if required {
+        if author.name.is_none() {
+            author.name = Some(prompt("name", "What's your name?")?);
+        }
+
+        if author.email.is_none() {
+            author.email = Some(prompt("email", "What's your email address?")?);
+        }
+    }
+
+    Ok(author)
+}
+
+fn prompt(field: &str, message: &str) -> Result<String> {
+    if !prompts::is_interactive() {
+        bail!(
+            "failed to find the author {field}, please set it in the global settings, the git \
+             config or through the environment"
+        );
+    }
+
+    Text::new(message)
+        .with_help_message("used as author information in the generated project")
+        .with_validator(validators::Required)
+        .prompt()
+        .map_err(Into::into)
+}
@@ -0,0 +1,278 @@
+//! Pre-defined variables, that are available in the context of every template, and a list
+//! describing all of them.
+
+use std::{
+    env::{self, consts},
+    process::Command,
+};
+
+use anyhow::{Context, Result};
+use camino::{Utf8Path, Utf8PathBuf};
+use chrono::{Datelike, Local, SecondsFormat};
+use tera::Context as TeraContext;
+
+/// Description of a single pre-defined variable.
+pub struct Builtin {
+    /// Name of the variable within the context.
+    pub name: &'static str,
+    /// Type of the value, one of `string`, `number` or `bool`.
+    pub ty: &'static str,
+    /// Short description of the value.
+    pub description: &'static str,
+}
+
+/// All pre-defined variables, that cargo-hatch puts into the context of each template.
+pub static BUILTINS: &[Builtin] = &[
+    Builtin {
+        name: "project_name",
+        ty: "string",
+        description: "Name of the generated project, derived from the target directory",
+    },
+    Builtin {
+        name: "git_author",
+        ty: "string",
+        description: "Author name and email in the form `name <email>`",
+    },
+    Builtin {
+        name: "git_name",
+        ty: "string",
+        description: "Author name, from the global config, environment or Git config",
+    },
+    Builtin {
+        name: "git_email",
+        ty: "string",
+        description: "Author email, from the global config, environment or Git config",
+    },
+    Builtin {
+        name: "crate_type",
+        ty: "string",
+        description: "Selected crate type, like `bin`, `lib`, `bin+lib` or `none`",
+    },
+    Builtin {
+        name: "crate_bin",
+        ty: "bool",
+        description: "Whether the crate type is `bin` or `bin+lib`",
+    },
+    Builtin {
+        name: "crate_lib",
+        ty: "bool",
+        description: "Whether the crate type is `lib` or `bin+lib`",
+    },
+    Builtin {
+        name: "crate_proc_macro",
+        ty: "bool",
+        description: "Whether the crate type is `proc-macro`",
+    },
+    Builtin {
+        name: "crate_cdylib",
+        ty: "bool",
+        description: "Whether the crate type is `cdylib`",
+    },
+    Builtin {
+        name: "crate_staticlib",
+        ty: "bool",
+        description: "Whether the crate type is `staticlib`",
+    },
+    Builtin {
+        name: "year",
+        ty: "number",
+        description: "Current year in local time",
+    },
+    Builtin {
+        name: "date",
+        ty: "string",
+        description: "Current date in local time, formatted as `YYYY-MM-DD`",
+    },
+    Builtin {
+        name: "datetime",
+        ty: "string",
+        description: "Current date and time in local time, formatted as RFC 3339",
+    },
+    Builtin {
+        name: "rustc_version",
+        ty: "string",
+        description: "Version of the local `rustc`, if found",
+    },
+    Builtin {
+        name: "cargo_version",
+        ty: "string",
+        description: "Version of the local `cargo`, if found",
+    },
+    Builtin {
+        name: "rust_edition",
+        ty: "string",
+        description: "Latest edition supported by the local `rustc`, if found",
+    },
+    Builtin {
+        name: "hatch_version",
+        ty: "string",
+        description: "Version of cargo-hatch that generated the project",
+    },
+    Builtin {
+        name: "os",
+        ty: "string",
+        description: "Operating system, like `linux`, `macos` or `windows`",
+    },
+    Builtin {
+        name: "arch",
+        ty: "string",
+        description: "CPU architecture, like `x86_64` or `aarch64`",
+    },
+    Builtin {
+        name: "template_source",
+        ty: "string",
+        description: "Git URL or local path of the template",
+    },
+    Builtin {
+        name: "template_folder",
+        ty: "string",
+        description: "Sub-folder of the template within its repository, if any",
+    },
+    Builtin {
+        name: "template_commit",
+        ty: "string",
+        description: "Git commit hash of the template, if it is located in a Git repository",
+    },
+];
+
+/// Information about where a template was loaded from.
+pub struct TemplateSource {
+    /// Remote Git URL or local path of the template.
+    pub source: String,
+    /// Optional sub-folder within the source, that contains the template.
+    pub folder: Option<Utf8PathBuf>,
+    /// Commit hash of the template, if it is located in a Git repository.
+    pub commit: Option<String>,
+}
+
+impl TemplateSource {
+    /// Unique key of the template, combining the source and the folder. Local paths are made
+    /// absolute, so the key is the same regardless of the current working directory.
+    #[must_use]
+    pub fn key(&self) -> String {
+        let source = Utf8Path::new(&self.source)
+            .canonicalize_utf8()
+            .map_or_else(|_| self.source.clone(), Utf8PathBuf::into_string);
+
+        match &self.folder {
+            Some(folder) => format!("{source}#{folder}"),
+            None => source,
+        }
+    }
+}
+
+/// Add the current date, toolchain, host and template source information to the context.
+pub fn fill(ctx: &mut TeraContext, source: &TemplateSource) -> Result<()> {
+    let now = Local::now();
+
+    ctx.try_insert("year", &now.year())
+        .context("failed adding value to context")?;
+    ctx.try_insert("date", &now.format("%Y-%m-%d").to_string())
+        .context("failed adding value to context")?;
+    ctx.try_insert("datetime", &now.to_rfc3339_opts(SecondsFormat::Secs, false))
+        .context("failed adding value to context")?;
+
+    let rustc_version = tool_version("RUSTC", "rustc");
+
+    if let Some(version) = &rustc_version {
+        ctx.try_insert("rustc_version", version)
+            .context("failed adding value to context")?;
+    }
+
+    if let Some(edition) = rustc_version.as_deref().and_then(edition) {
+        ctx.try_insert("rust_edition", edition)
+            .context("failed adding value to context")?;
+    }
+
+    if let Some(version) = tool_version("CARGO", "cargo") {
+        ctx.try_insert("cargo_version", &version)
+            .context("failed adding value to context")?;
+    }
+
+    ctx.try_insert("hatch_version", env!("CARGO_PKG_VERSION"))
+        .context("failed adding value to context")?;
+    ctx.try_insert("os", consts::OS)
+        .context("failed adding value to context")?;
+    ctx.try_insert("arch", consts::ARCH)
+        .context("failed adding value to context")?;
+
+    ctx.try_insert("template_source", &source.source)
+        .context("failed adding value to context")?;
+
+    if let Some(folder) = &source.folder {
+        ctx.try_insert("template_folder", folder)
+            .context("failed adding value to context")?;
+    }
+
+    if let Some(commit) = &source.commit {
+        ctx.try_insert("template_commit", commit)
+            .context("failed adding value to context")?;
+    }
+
+    Ok(())
+}
+
+/// Get the version of a Rust tool, by running it with the `--version` flag. The binary is taken
+/// from the given environment variable, as set by `cargo` when running sub-commands, or else
+/// searched by its default name.
+fn tool_version(var: &str, default: &str) -> Option<String> {
+    let bin = env::var_os(var).unwrap_or_else(|| default.into());
+    let output = Command::new(bin).arg("--version").output().ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    parse_version(&String::from_utf8(output.stdout).ok()?).map(ToOwned::to_owned)
+}
+
+/// Extract the version from an output like `rustc 1.70.0 (90c541806 2023-05-31)`.
+fn parse_version(output: &str) -> Option<&str> {
+    output
+        .split_whitespace()
+        .nth(1)
+        .filter(|version| version.parse::<semver::Version>().is_ok())
+}
+
+/// Determine the latest stable edition, that is supported by the given `rustc` version.
+fn edition(rustc_version: &str) -> Option<&'static str> {
+    let version = rustc_version.parse::<semver::Version>().ok()?;
+
+    Some(match (version.major, version.minor) {
+        (1, 85..) => "2024",
+        (1, 56..) => "2021",
+        (1, 31..) => "2018",
+        _ => "2015",
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_tool_versions() {
+        assert_eq!(
+            Some("1.70.0"),
+            parse_version("rustc 1.70.0 (90c541806 2023-05-31)")
+        );
+        assert_eq!(
+            Some("1.70.0"),
+            parse_version("cargo 1.70.0 (ec8a8a0ca 2023-04-25)")
+        );
+        assert_eq!(
+            Some("1.72.0-nightly"),
+            parse_version("rustc 1.72.0-nightly (871b59520 2023-05-31)")
+        );
+        assert_eq!(None, parse_version("command not found"));
+    }
+
+    #[test]
+    fn editions() {
+        assert_eq!(Some("2015"), edition("1.30.0"));
+        assert_eq!(Some("2018"), edition("1.55.0"));
+        assert_eq!(Some("2021"), edition("1.70.0"));
+        assert_eq!(Some("2024"), edition("1.85.0"));
+        assert_eq!(None, edition("1.70"));
+    }
+}
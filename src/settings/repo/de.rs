@@ -6,7 +6,10 @@ use std::{
     str::FromStr,
 };
 
-use serde::de::{Deserializer, Visitor};
+use serde::{
+    de::{Deserializer, Visitor},
+    Deserialize,
+};
 
 /// Deserialize any type from its text form, that implements [`FromStr`].
 pub fn from_str<'de, D, T>(deserializer: D) -> Result<T, D::Error>
@@ -18,11 +21,30 @@ where
     deserializer.deserialize_str(FromStrVisitor { ty: PhantomData })
 }
 
+/// Deserialize a list of values, that can alternatively be defined as a single value.
+pub fn one_or_many<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany<T> {
+        One(T),
+        Many(Vec<T>),
+    }
+
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(value) => vec![value],
+        OneOrMany::Many(values) => values,
+    })
+}
+
 struct FromStrVisitor<T> {
     ty: PhantomData<T>,
 }
 
-impl<'de, T> Visitor<'de> for FromStrVisitor<T>
+impl<T> Visitor<'_> for FromStrVisitor<T>
 where
     T: FromStr,
     T::Err: Display,
@@ -2,10 +2,32 @@
 
 use std::collections::HashSet;
 
-use anyhow::{bail, Result};
+use anyhow::{bail, Context, Result};
+use tera::Context as TeraContext;
 
 use crate::settings::global::{DefaultSetting, DefaultValue};
 
+/// Create a default setting from the value that is currently stored in the context for the given
+/// setting, so it can be used as pre-selection when prompting again.
+pub fn from_context(ctx: &TeraContext, name: &str) -> Result<Option<DefaultSetting>> {
+    let Some(value) = ctx.get(name).cloned() else {
+        return Ok(None);
+    };
+
+    from_value(value)
+        .map(Some)
+        .with_context(|| format!("invalid value for setting `{name}` in context"))
+}
+
+/// Create a default setting from a plain value. The prompt is not skipped, so the value is only
+/// pre-selected.
+pub fn from_value(value: tera::Value) -> tera::Result<DefaultSetting> {
+    Ok(DefaultSetting {
+        value: tera::from_value(value)?,
+        skip_prompt: false,
+    })
+}
+
 pub fn get_bool(default: DefaultSetting) -> Result<bool> {
     if let DefaultValue::Bool(value) = default.value {
         Ok(value)
@@ -39,34 +61,44 @@ pub fn get_number(default: DefaultSetting) -> Result<i64> {
     }
 }
 
+/// Get a float value, that may also be given as integer, as TOML doesn't treat `1` as float.
+#[allow(clippy::cast_precision_loss)]
 pub fn get_float(default: DefaultSetting) -> Result<f64> {
-    if let DefaultValue::Float(value) = default.value {
+    match default.value {
+        DefaultValue::Float(value) => Ok(value),
+        DefaultValue::Number(value) => Ok(value as f64),
+        value => bail!("invalid default value for float setting ({value:?})"),
+    }
+}
+
+pub fn get_list(default: DefaultSetting) -> Result<String> {
+    if let DefaultValue::String(value) = default.value {
         Ok(value)
     } else {
         bail!(
-            "invalid default value for float setting ({:?})",
+            "invalid default value for list setting ({:?})",
             default.value
         )
     }
 }
 
-pub fn get_list(default: DefaultSetting) -> Result<String> {
-    if let DefaultValue::List(value) = default.value {
-        Ok(value)
+pub fn get_multi_list(default: DefaultSetting) -> Result<HashSet<String>> {
+    if let DefaultValue::Array(value) = default.value {
+        Ok(value.into_iter().collect())
     } else {
         bail!(
-            "invalid default value for list setting ({:?})",
+            "invalid default value for multi-list setting ({:?})",
             default.value
         )
     }
 }
 
-pub fn get_multi_list(default: DefaultSetting) -> Result<HashSet<String>> {
-    if let DefaultValue::MultiList(value) = default.value {
+pub fn get_license(default: DefaultSetting) -> Result<String> {
+    if let DefaultValue::String(value) = default.value {
         Ok(value)
     } else {
         bail!(
-            "invalid default value for multi-list setting ({:?})",
+            "invalid default value for license setting ({:?})",
             default.value
         )
     }
@@ -1,23 +1,33 @@
 use std::{
+    borrow::Borrow,
     collections::{HashMap, HashSet},
-    fmt::Display,
+    fmt::{self, Display},
     fs,
-    hash::BuildHasher,
-    iter::FromIterator,
+    hash::{BuildHasher, Hash, Hasher},
     str::FromStr,
 };
 
 use anyhow::{bail, Context, Result};
 use camino::{Utf8Path, Utf8PathBuf};
-use git2::Config as GitConfig;
+use crossterm::style::Stylize;
 use indexmap::{IndexMap, IndexSet};
 use num_traits::Num;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use tera::{Context as TeraContext, Tera};
 
-use super::global::DefaultSetting;
+use self::prompts::Prompt;
+pub use self::{
+    author::find as find_author,
+    builtins::{Builtin, TemplateSource, BUILTINS},
+};
+use super::{
+    answers::Answers,
+    global::{Author, DefaultSetting, DefaultValue},
+};
 
+mod author;
+mod builtins;
 mod de;
 mod defaults;
 mod prompts;
@@ -25,9 +35,28 @@ mod validators;
 
 #[derive(Deserialize)]
 pub struct RepoSettings {
-    crate_type: Option<CrateType>,
+    #[serde(default, deserialize_with = "de::one_or_many")]
+    crate_type: Vec<CrateType>,
     #[serde(default)]
     pub ignore: Vec<IgnorePattern>,
+    #[serde(default)]
+    pub computed: IndexMap<String, ComputedSetting>,
+    #[serde(default)]
+    pub validate: Vec<ValidationRule>,
+    #[serde(default)]
+    pub groups: IndexMap<String, Group>,
+    #[serde(default)]
+    pub permissions: IndexMap<String, FileMode>,
+    #[serde(default)]
+    pub raw: Vec<String>,
+    #[serde(default)]
+    pub template: Vec<String>,
+    #[serde(default)]
+    pub delimiters: Vec<DelimiterRule>,
+    #[serde(default)]
+    pub skip_empty: bool,
+    #[serde(default)]
+    pub foreach: Vec<ForeachRule>,
     #[serde(flatten)]
     pub args: IndexMap<String, RepoSetting>,
 }
@@ -38,17 +67,253 @@ pub struct IgnorePattern {
     pub condition: Option<String>,
 }
 
+/// Files matching any of the glob patterns are generated once for each element of a list.
+#[derive(Deserialize)]
+pub struct ForeachRule {
+    pub paths: Vec<String>,
+    /// Name of the setting, that contains the list of elements.
+    pub items: String,
+}
+
+/// Origin of externally provided default values, used to point to it in messages.
+#[derive(Clone, Copy, Debug)]
+pub enum DefaultSource<'a> {
+    Bookmark(&'a str),
+    Preset(&'a str),
+}
+
+impl Display for DefaultSource<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bookmark(name) => write!(f, "bookmark `{name}`"),
+            Self::Preset(name) => write!(f, "preset `{name}`"),
+        }
+    }
+}
+
+/// Unix file mode, given in its octal text form like `0755`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct FileMode(pub u32);
+
+impl TryFrom<String> for FileMode {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        u32::from_str_radix(&value, 8)
+            .ok()
+            .filter(|mode| *mode <= 0o7777)
+            .map(Self)
+            .ok_or_else(|| {
+                format!("invalid file mode `{value}`, expected an octal value like `0755`")
+            })
+    }
+}
+
+/// Alternate delimiters for files matching any of the glob patterns.
+#[derive(Deserialize)]
+pub struct DelimiterRule {
+    pub paths: Vec<String>,
+    #[serde(flatten)]
+    pub delimiters: Delimiters,
+}
+
+/// Alternate start and end delimiters for the different kinds of template tags. Kinds that aren't
+/// set keep the default Tera delimiters.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Delimiters {
+    /// Replacement for `{{` and `}}`.
+    pub variable: Option<(String, String)>,
+    /// Replacement for `{%` and `%}`.
+    pub block: Option<(String, String)>,
+    /// Replacement for `{#` and `#}`.
+    pub comment: Option<(String, String)>,
+}
+
+impl Delimiters {
+    /// Check that all delimiters are set and that the start delimiters can be told apart, both from
+    /// each other and from the regular delimiters of kinds that aren't replaced. Returns the
+    /// reason as message for the first problem found.
+    #[must_use]
+    pub fn validate(&self) -> Option<&'static str> {
+        let kinds = [
+            (&self.variable, "{{"),
+            (&self.block, "{%"),
+            (&self.comment, "{#"),
+        ];
+        let pairs = kinds.iter().filter_map(|(pair, _)| pair.as_ref());
+
+        if pairs
+            .clone()
+            .any(|(start, end)| start.is_empty() || end.is_empty())
+        {
+            return Some("delimiters must not be empty");
+        }
+
+        let ambiguous = |a: &str, b: &str| a.starts_with(b) || b.starts_with(a);
+
+        let starts = pairs.map(|(start, _)| start).collect::<Vec<_>>();
+        if starts
+            .iter()
+            .enumerate()
+            .any(|(i, a)| starts[i + 1..].iter().any(|b| ambiguous(a, b)))
+        {
+            return Some("start delimiters must be distinguishable from each other");
+        }
+
+        let regular = kinds
+            .iter()
+            .filter(|(pair, _)| pair.is_none())
+            .map(|(_, start)| *start)
+            .collect::<Vec<_>>();
+        if starts
+            .iter()
+            .any(|a| regular.iter().any(|b| ambiguous(a, b)))
+        {
+            return Some(
+                "start delimiters must be distinguishable from the regular delimiters of kinds \
+                 that aren't replaced",
+            );
+        }
+
+        None
+    }
+}
+
+/// A value that is not prompted from the user, but derived from other values of the context through
+/// a Tera template.
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum ComputedSetting {
+    /// Short form, that only defines the template, which is evaluated after all prompts.
+    Template(String),
+    /// Full form, that can optionally define after which setting the template is evaluated, and
+    /// the type the result is converted to.
+    Full {
+        value: String,
+        after: Option<String>,
+        #[serde(rename = "type")]
+        ty: Option<ComputedType>,
+    },
+}
+
+/// Explicit type of a computed value, that its result is parsed as.
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ComputedType {
+    Bool,
+    String,
+    Number,
+    Float,
+}
+
+impl ComputedSetting {
+    fn value(&self) -> &str {
+        match self {
+            Self::Template(value) | Self::Full { value, .. } => value,
+        }
+    }
+
+    fn after(&self) -> Option<&str> {
+        match self {
+            Self::Template(_) => None,
+            Self::Full { after, .. } => after.as_deref(),
+        }
+    }
+
+    fn ty(&self) -> Option<ComputedType> {
+        match self {
+            Self::Template(_) => None,
+            Self::Full { ty, .. } => *ty,
+        }
+    }
+}
+
+/// A rule that checks the collected values in relation to each other, after all settings were
+/// filled.
+#[derive(Deserialize)]
+pub struct ValidationRule {
+    /// Tera template that must evaluate to `true` for the values to be considered valid.
+    condition: String,
+    /// Message shown to the user, if the condition fails.
+    message: String,
+    /// Settings that are prompted again, if the condition fails.
+    #[serde(default)]
+    settings: Vec<String>,
+}
+
+/// A section of settings, that are shown together under a common header.
+#[derive(Deserialize)]
+pub struct Group {
+    /// Header, that is shown before the first setting of the group.
+    title: String,
+    /// Optional question, that is asked before any of the group's settings. If answered with no,
+    /// the settings are not prompted for, and their default values are used instead.
+    question: Option<String>,
+    /// Pre-selected answer to the question.
+    default: Option<bool>,
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum CrateType {
     Bin,
     Lib,
+    #[serde(rename = "bin+lib")]
+    BinLib,
+    #[serde(rename = "proc-macro")]
+    ProcMacro,
+    Cdylib,
+    Staticlib,
+    /// The template is not a Rust crate.
+    None,
+}
+
+impl CrateType {
+    const ALL: [Self; 7] = [
+        Self::Bin,
+        Self::Lib,
+        Self::BinLib,
+        Self::ProcMacro,
+        Self::Cdylib,
+        Self::Staticlib,
+        Self::None,
+    ];
+
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Bin => "bin",
+            Self::Lib => "lib",
+            Self::BinLib => "bin+lib",
+            Self::ProcMacro => "proc-macro",
+            Self::Cdylib => "cdylib",
+            Self::Staticlib => "staticlib",
+            Self::None => "none",
+        }
+    }
+
+    /// Boolean flags for the context, telling whether a certain kind of crate is generated.
+    fn flags(self) -> [(&'static str, bool); 5] {
+        [
+            ("crate_bin", matches!(self, Self::Bin | Self::BinLib)),
+            ("crate_lib", matches!(self, Self::Lib | Self::BinLib)),
+            ("crate_proc_macro", self == Self::ProcMacro),
+            ("crate_cdylib", self == Self::Cdylib),
+            ("crate_staticlib", self == Self::Staticlib),
+        ]
+    }
 }
 
 #[derive(Deserialize)]
 pub struct RepoSetting {
     description: String,
+    help: Option<String>,
+    placeholder: Option<String>,
+    example: Option<String>,
     condition: Option<String>,
+    group: Option<String>,
     #[serde(flatten)]
     ty: SettingType,
 }
@@ -62,6 +327,7 @@ pub enum SettingType {
     Float(NumberSetting<f64>),
     List(ListSetting),
     MultiList(MultiListSetting),
+    License(LicenseSetting),
 }
 
 trait Setting<D> {
@@ -69,9 +335,14 @@ trait Setting<D> {
     fn validate(&self) -> Option<&'static str> {
         None
     }
+    /// Check a value, that was provided from outside the template like bookmark defaults, for
+    /// validity.
+    fn check(&self, _value: &D) -> Result<()> {
+        Ok(())
+    }
 }
 
-#[derive(Deserialize)]
+#[derive(Clone, Deserialize)]
 pub struct BoolSetting {
     default: Option<bool>,
 }
@@ -82,10 +353,11 @@ impl Setting<bool> for BoolSetting {
     }
 }
 
-#[derive(Deserialize)]
+#[derive(Clone, Deserialize)]
 pub struct StringSetting {
     default: Option<String>,
-    validator: Option<StringValidator>,
+    #[serde(default, alias = "validator", deserialize_with = "de::one_or_many")]
+    validators: Vec<Validator>,
 }
 
 impl Setting<String> for StringSetting {
@@ -94,22 +366,96 @@ impl Setting<String> for StringSetting {
     }
 }
 
-#[derive(Deserialize)]
-#[serde(rename_all = "snake_case")]
+/// Validator for string settings, with an optional custom error message that replaces the
+/// validator's default message.
+#[derive(Clone, Deserialize)]
+#[serde(try_from = "RawValidator")]
+pub struct Validator {
+    kind: StringValidator,
+    message: Option<String>,
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
 pub enum StringValidator {
     Crate,
     Ident,
     Semver,
     SemverReq,
-    #[serde(deserialize_with = "de::from_str")]
-    Regex(Regex),
+    Email,
+    Url,
+    Spdx,
+    Regex {
+        #[serde(deserialize_with = "de::from_str")]
+        pattern: Regex,
+    },
+    MinLength {
+        length: usize,
+    },
+    MaxLength {
+        length: usize,
+    },
+    NotIn {
+        values: Vec<String>,
+    },
+}
+
+/// The different forms, a [`Validator`] can be defined in.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawValidator {
+    /// Name of a validator without any parameters, like `"crate"`.
+    Name(String),
+    /// Table with the validator type, its parameters and optional message, like
+    /// `{ type = "min_length", length = 3, message = "too short" }`.
+    Full {
+        #[serde(flatten)]
+        kind: StringValidator,
+        message: Option<String>,
+    },
+    /// Short form for regular expressions, like `validator.regex = "^[a-z]+$"`.
+    Regex {
+        #[serde(deserialize_with = "de::from_str")]
+        regex: Regex,
+    },
+}
+
+impl TryFrom<RawValidator> for Validator {
+    type Error = String;
+
+    fn try_from(value: RawValidator) -> Result<Self, Self::Error> {
+        let (kind, message) = match value {
+            RawValidator::Name(name) => {
+                let kind = match name.as_str() {
+                    "crate" => StringValidator::Crate,
+                    "ident" => StringValidator::Ident,
+                    "semver" => StringValidator::Semver,
+                    "semver_req" => StringValidator::SemverReq,
+                    "email" => StringValidator::Email,
+                    "url" => StringValidator::Url,
+                    "spdx" => StringValidator::Spdx,
+                    _ => {
+                        return Err(format!(
+                            "unknown validator `{name}` (validators with parameters must be \
+                             defined as table)"
+                        ))
+                    }
+                };
+                (kind, None)
+            }
+            RawValidator::Full { kind, message } => (kind, message),
+            RawValidator::Regex { regex } => (StringValidator::Regex { pattern: regex }, None),
+        };
+
+        Ok(Self { kind, message })
+    }
 }
 
 pub trait Number: Num + Copy + Display + FromStr + PartialOrd + Serialize {}
 
 impl<T: Num + Copy + Display + FromStr + PartialOrd + Serialize> Number for T {}
 
-#[derive(Deserialize)]
+#[derive(Clone, Deserialize)]
 pub struct NumberSetting<T: Number> {
     min: T,
     max: T,
@@ -137,12 +483,102 @@ impl<T: Number> Setting<T> for NumberSetting<T> {
     }
 }
 
-#[derive(Deserialize)]
+#[derive(Clone, Deserialize)]
 pub struct ListSetting {
-    values: IndexSet<String>,
+    values: IndexSet<Choice>,
     default: Option<String>,
 }
 
+/// A single option of a list or multi-list setting.
+///
+/// Choices are compared and hashed only by their value, so they can be looked up by it.
+#[derive(Clone, Deserialize)]
+#[serde(from = "RawChoice")]
+pub struct Choice {
+    /// Machine value that is put into the context.
+    value: String,
+    /// Human-readable label shown in the prompt instead of the value.
+    label: Option<String>,
+    /// Additional explanation shown next to the label.
+    description: Option<String>,
+    /// Tera template, that decides whether the option is shown at all.
+    condition: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawChoice {
+    Value(String),
+    Full {
+        value: String,
+        label: Option<String>,
+        description: Option<String>,
+        condition: Option<String>,
+    },
+}
+
+impl From<RawChoice> for Choice {
+    fn from(value: RawChoice) -> Self {
+        match value {
+            RawChoice::Value(value) => value.into(),
+            RawChoice::Full {
+                value,
+                label,
+                description,
+                condition,
+            } => Self {
+                value,
+                label,
+                description,
+                condition,
+            },
+        }
+    }
+}
+
+impl From<String> for Choice {
+    fn from(value: String) -> Self {
+        Self {
+            value,
+            label: None,
+            description: None,
+            condition: None,
+        }
+    }
+}
+
+impl PartialEq for Choice {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl Eq for Choice {}
+
+impl Hash for Choice {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.value.hash(state);
+    }
+}
+
+impl Borrow<str> for Choice {
+    fn borrow(&self) -> &str {
+        &self.value
+    }
+}
+
+impl Display for Choice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.label.as_deref().unwrap_or(&self.value))?;
+
+        if let Some(description) = &self.description {
+            write!(f, " - {description}")?;
+        }
+
+        Ok(())
+    }
+}
+
 impl Setting<String> for ListSetting {
     fn set_default(&mut self, default: String) {
         self.default = Some(default);
@@ -152,15 +588,38 @@ impl Setting<String> for ListSetting {
         let Self { values, default } = self;
 
         default.as_ref().and_then(|default| {
-            (!values.contains(default)).then_some("default value isn't part of the possible values")
+            (!values.contains(default.as_str()))
+                .then_some("default value isn't part of the possible values")
         })
     }
+
+    fn check(&self, value: &String) -> Result<()> {
+        if !self.values.contains(value.as_str()) {
+            bail!("`{value}` isn't one of the possible values");
+        }
+
+        Ok(())
+    }
 }
 
-#[derive(Deserialize)]
+#[derive(Clone, Deserialize)]
 pub struct MultiListSetting {
-    values: IndexSet<String>,
+    values: IndexSet<Choice>,
     default: Option<HashSet<String>>,
+    min: Option<usize>,
+    max: Option<usize>,
+    #[serde(default)]
+    exclusive: Vec<Vec<String>>,
+}
+
+impl MultiListSetting {
+    fn selection(&self) -> validators::Selection {
+        validators::Selection {
+            min: self.min,
+            max: self.max,
+            exclusive: self.exclusive.clone(),
+        }
+    }
 }
 
 impl Setting<HashSet<String>> for MultiListSetting {
@@ -169,15 +628,243 @@ impl Setting<HashSet<String>> for MultiListSetting {
     }
 
     fn validate(&self) -> Option<&'static str> {
-        let Self { values, default } = self;
+        let Self {
+            values,
+            default,
+            min,
+            max,
+            exclusive,
+        } = self;
+
+        if matches!((min, max), (Some(min), Some(max)) if min > max) {
+            return Some("minimum selection count is greater than the maximum");
+        }
+
+        if min.map_or(false, |min| min > values.len()) {
+            return Some("minimum selection count exceeds the amount of possible values");
+        }
+
+        if exclusive
+            .iter()
+            .flatten()
+            .any(|value| !values.contains(value.as_str()))
+        {
+            return Some("one of the exclusive values isn't part of the possible values");
+        }
 
         default.as_ref().and_then(|default| {
-            default
-                .iter()
-                .any(|def| !values.contains(def))
-                .then_some("one of the default values isn't part of the possible values")
+            if default.iter().any(|def| !values.contains(def.as_str())) {
+                Some("one of the default values isn't part of the possible values")
+            } else if self
+                .selection()
+                .check(&default.iter().map(String::as_str).collect::<Vec<_>>())
+                .is_some()
+            {
+                Some("default values don't satisfy the selection constraints")
+            } else {
+                None
+            }
         })
     }
+
+    fn check(&self, value: &HashSet<String>) -> Result<()> {
+        if let Some(value) = value.iter().find(|v| !self.values.contains(v.as_str())) {
+            bail!("`{value}` isn't one of the possible values");
+        }
+
+        match self
+            .selection()
+            .check(&value.iter().map(String::as_str).collect::<Vec<_>>())
+        {
+            Some(message) => bail!("invalid selection: {message}"),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Clone, Deserialize)]
+pub struct LicenseSetting {
+    #[serde(default)]
+    values: IndexSet<String>,
+    default: Option<String>,
+    #[serde(default = "default_true")]
+    files: bool,
+}
+
+const fn default_true() -> bool {
+    true
+}
+
+impl Setting<String> for LicenseSetting {
+    fn set_default(&mut self, default: String) {
+        self.default = Some(default);
+    }
+
+    fn validate(&self) -> Option<&'static str> {
+        let Self {
+            values, default, ..
+        } = self;
+
+        values
+            .iter()
+            .chain(default)
+            .any(|value| spdx::Expression::parse(value).is_err())
+            .then_some("one of the values isn't a valid SPDX license expression")
+            .or_else(|| {
+                default.as_ref().and_then(|default| {
+                    (!values.is_empty() && !values.contains(default))
+                        .then_some("default value isn't part of the possible values")
+                })
+            })
+    }
+
+    fn check(&self, value: &String) -> Result<()> {
+        spdx::Expression::parse(value)
+            .map_err(|e| anyhow::anyhow!("{}", e.reason))
+            .with_context(|| format!("`{value}` is not a valid SPDX license expression"))?;
+
+        if !self.values.is_empty() && !self.values.contains(value) {
+            bail!("`{value}` isn't one of the possible values");
+        }
+
+        Ok(())
+    }
+}
+
+impl RepoSettings {
+    /// Check whether any of the templates in the settings, like conditions or computed values,
+    /// mention one of the given variable names.
+    ///
+    /// License settings that generate files are considered to reference `git_name`, as it is
+    /// used in the copyright notice.
+    #[must_use]
+    pub fn references(&self, names: &[&str]) -> bool {
+        let contains = |template: &str| references(template, names);
+        let choices = |choices: &IndexSet<Choice>| {
+            choices
+                .iter()
+                .any(|choice| choice.condition.as_deref().map_or(false, contains))
+        };
+
+        self.args.values().any(|setting| {
+            setting.condition.as_deref().map_or(false, contains)
+                || match &setting.ty {
+                    SettingType::List(s) => choices(&s.values),
+                    SettingType::MultiList(s) => choices(&s.values),
+                    SettingType::License(s) => s.files && names.contains(&"git_name"),
+                    _ => false,
+                }
+        }) || self.computed.values().any(|c| contains(c.value()))
+            || self.validate.iter().any(|rule| contains(&rule.condition))
+            || self
+                .ignore
+                .iter()
+                .any(|rule| rule.condition.as_deref().map_or(false, contains))
+    }
+
+    /// Get the license expressions, that were selected for all license settings that generate
+    /// license files.
+    #[must_use]
+    pub fn licenses(&self, ctx: &TeraContext) -> Vec<String> {
+        self.args
+            .iter()
+            .filter(|(_, setting)| matches!(&setting.ty, SettingType::License(s) if s.files))
+            .filter_map(|(name, _)| ctx.get(name)?.as_str().map(ToOwned::to_owned))
+            .collect()
+    }
+
+    /// Check default values from outside the template, like bookmarks, to fit the type and
+    /// constraints of the setting or group question they're applied to. Values for unknown
+    /// settings are ignored.
+    pub fn check_defaults<H: BuildHasher>(
+        &self,
+        defaults: &HashMap<String, DefaultSetting, H>,
+    ) -> Result<()> {
+        for (name, default) in defaults {
+            self.check_default(name, default)
+                .with_context(|| format!("invalid default value for setting `{name}`"))?;
+        }
+
+        Ok(())
+    }
+
+    fn check_default(&self, name: &str, default: &DefaultSetting) -> Result<()> {
+        if let Some(setting) = self.args.get(name) {
+            setting.check_default(default)
+        } else if self.groups.contains_key(name) {
+            defaults::get_bool(default.clone()).map(drop)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Find the setting or group, whose name is most similar to the given one. Used to give
+    /// suggestions for misspelled names.
+    #[must_use]
+    pub fn suggest(&self, name: &str) -> Option<&str> {
+        self.args
+            .keys()
+            .chain(self.groups.keys())
+            .map(|candidate| (strsim::jaro_winkler(name, candidate), candidate))
+            .filter(|(similarity, _)| *similarity > 0.8)
+            .max_by(|(a, _), (b, _)| a.total_cmp(b))
+            .map(|(_, candidate)| candidate.as_str())
+    }
+
+    /// Collect the answers to all settings and group questions from the context, so they can be
+    /// remembered for the next run.
+    #[must_use]
+    pub fn answers(&self, ctx: &TeraContext) -> Answers {
+        self.groups
+            .keys()
+            .chain(self.args.keys())
+            .filter_map(|name| Some((name.clone(), ctx.get(name)?.clone())))
+            .collect()
+    }
+
+    /// Turn the remembered answers of a previous run into defaults, that pre-select the previous
+    /// answers without skipping the prompt. Answers that don't fit the settings anymore, for
+    /// example because the template changed in the meantime, are dropped.
+    #[must_use]
+    pub fn remembered_defaults(&self, answers: Answers) -> HashMap<String, DefaultSetting> {
+        answers
+            .into_iter()
+            .filter_map(|(name, value)| {
+                if !self.args.contains_key(&name) && !self.groups.contains_key(&name) {
+                    return None;
+                }
+
+                let default = defaults::from_value(value).ok()?;
+                self.check_default(&name, &default).ok()?;
+
+                Some((name, default))
+            })
+            .collect()
+    }
+
+    /// Merge the defaults of a bookmark and presets with the remembered answers of a previous
+    /// run. Later sources override earlier ones, so presets take precedence over the bookmark,
+    /// while remembered answers only fill in settings without any other default.
+    pub fn merge_defaults<H: BuildHasher>(
+        &self,
+        sources: &[(DefaultSource<'_>, &HashMap<String, DefaultSetting, H>)],
+        remembered: Answers,
+    ) -> Result<HashMap<String, DefaultSetting>> {
+        let mut defaults = HashMap::new();
+
+        for (source, values) in sources {
+            self.check_defaults(values)
+                .with_context(|| format!("invalid defaults in {source}"))?;
+
+            defaults.extend(values.iter().map(|(k, v)| (k.clone(), v.clone())));
+        }
+
+        for (name, default) in self.remembered_defaults(remembered) {
+            defaults.entry(name).or_insert(default);
+        }
+
+        Ok(defaults)
+    }
 }
 
 impl RepoSetting {
@@ -187,41 +874,73 @@ impl RepoSetting {
     pub fn validate(&self) -> Option<&'static str> {
         match &self.ty {
             SettingType::Bool(_) | SettingType::String(_) => None,
-            SettingType::Number(setting) => Self::validate_number(setting),
-            SettingType::Float(setting) => Self::validate_number(setting),
-            SettingType::List(ListSetting { values, default }) => {
-                default.as_ref().and_then(|default| {
-                    (!values.contains(default))
-                        .then_some("default value isn't part of the possible values")
-                })
-            }
-            SettingType::MultiList(MultiListSetting { values, default }) => {
-                default.as_ref().and_then(|default| {
-                    default
-                        .iter()
-                        .any(|def| !values.contains(def))
-                        .then_some("one of the default values isn't part of the possible values")
-                })
-            }
+            SettingType::Number(setting) => setting.validate(),
+            SettingType::Float(setting) => setting.validate(),
+            SettingType::List(setting) => setting.validate(),
+            SettingType::MultiList(setting) => setting.validate(),
+            SettingType::License(setting) => setting.validate(),
         }
     }
 
-    fn validate_number<T: Number>(
-        NumberSetting { min, max, default }: &NumberSetting<T>,
-    ) -> Option<&'static str> {
-        (min >= max)
-            .then_some("minimum is greater or equal the maximum value")
-            .or_else(|| {
-                default
-                    .as_ref()
-                    .map(|d| !(*min..*max).contains(d))
-                    .and_then(|invalid| {
-                        invalid.then_some("default value is not within the min/max range")
-                    })
-            })
+    /// Check whether a default value fits the type of this setting, and satisfies its
+    /// constraints.
+    fn check_default(&self, default: &DefaultSetting) -> Result<()> {
+        let default = default.clone();
+
+        match &self.ty {
+            SettingType::Bool(_) => defaults::get_bool(default).map(drop),
+            SettingType::String(_) => defaults::get_string(default).map(drop),
+            SettingType::Number(_) => defaults::get_number(default).map(drop),
+            SettingType::Float(_) => defaults::get_float(default).map(drop),
+            SettingType::List(setting) => setting.check(&defaults::get_list(default)?),
+            SettingType::MultiList(setting) => setting.check(&defaults::get_multi_list(default)?),
+            SettingType::License(setting) => setting.check(&defaults::get_license(default)?),
+        }
+    }
+
+    /// The default value, as defined in the template.
+    fn default_value(&self) -> Option<DefaultValue> {
+        match &self.ty {
+            SettingType::Bool(setting) => setting.default.map(DefaultValue::Bool),
+            SettingType::String(setting) => setting.default.clone().map(DefaultValue::String),
+            SettingType::Number(setting) => setting.default.map(DefaultValue::Number),
+            SettingType::Float(setting) => setting.default.map(DefaultValue::Float),
+            SettingType::List(setting) => setting.default.clone().map(DefaultValue::String),
+            SettingType::MultiList(setting) => setting
+                .default
+                .as_ref()
+                .map(|default| DefaultValue::Array(default.iter().cloned().collect())),
+            SettingType::License(setting) => setting.default.clone().map(DefaultValue::String),
+        }
+    }
+
+    /// Texts to show, when prompting the user for this setting.
+    fn prompt(&self) -> Prompt<'_> {
+        Prompt {
+            message: &self.description,
+            help: self.help.as_deref(),
+            placeholder: self.placeholder.as_deref(),
+            example: self.example.as_deref(),
+        }
     }
 }
 
+/// Check whether the template mentions any of the given variable names. Only whole identifiers are
+/// considered, so `git_name` doesn't match `my_git_name` or a field access like `author.git_name`.
+#[must_use]
+pub fn references(template: &str, names: &[&str]) -> bool {
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+
+    names.iter().any(|name| {
+        template.match_indices(name).any(|(start, _)| {
+            let before = template[..start].chars().next_back();
+            let after = template[start + name.len()..].chars().next();
+
+            !before.map_or(false, |c| is_ident(c) || c == '.') && !after.map_or(false, is_ident)
+        })
+    })
+}
+
 pub fn load(path: &Utf8Path) -> Result<RepoSettings> {
     let buf = fs::read(path.join(".hatch.toml")).context("failed reading hatch config file")?;
     let settings =
@@ -235,168 +954,1611 @@ pub fn load(path: &Utf8Path) -> Result<RepoSettings> {
         bail!("invalid setting `{name}`: {error}");
     }
 
-    Ok(settings)
-}
+    for (name, setting) in &settings.args {
+        if let Some(group) = &setting.group {
+            if !settings.groups.contains_key(group) {
+                bail!("setting `{name}` belongs to unknown group `{group}`");
+            }
+        }
+    }
 
-pub fn new_context(settings: &RepoSettings, project_name: &str) -> Result<TeraContext> {
-    let mut ctx = TeraContext::new();
+    if let Some(error) = settings
+        .delimiters
+        .iter()
+        .find_map(|rule| rule.delimiters.validate())
+    {
+        bail!("invalid delimiters: {error}");
+    }
+
+    for name in settings.groups.keys() {
+        if settings.args.contains_key(name) {
+            bail!("group `{name}` has the same name as a setting");
+        }
+    }
+
+    for (name, computed) in &settings.computed {
+        if settings.args.contains_key(name) {
+            bail!("computed value `{name}` has the same name as a setting");
+        }
+
+        if settings.groups.contains_key(name) {
+            bail!("computed value `{name}` has the same name as a group");
+        }
+
+        if let Some(after) = computed.after() {
+            if !settings.args.contains_key(after) {
+                bail!("computed value `{name}` is evaluated after unknown setting `{after}`");
+            }
+        }
+    }
+
+    for rule in &settings.foreach {
+        if !settings.args.contains_key(&rule.items) {
+            bail!("foreach rule refers to unknown setting `{}`", rule.items);
+        }
+    }
+
+    // Repeated files get the `item` and `index` variables, which would hide values of the same
+    // name.
+    if !settings.foreach.is_empty() {
+        if let Some(name) = ["item", "index"].into_iter().find(|name| {
+            settings.args.contains_key(*name)
+                || settings.computed.contains_key(*name)
+                || settings.groups.contains_key(*name)
+        }) {
+            bail!("setting `{name}` is shadowed by the variable of the same name in foreach files");
+        }
+    }
+
+    for rule in &settings.validate {
+        if let Some(name) = rule
+            .settings
+            .iter()
+            .find(|name| !settings.args.contains_key(*name))
+        {
+            bail!(
+                "validation rule `{}` refers to unknown setting `{name}`",
+                rule.message
+            );
+        }
+    }
+
+    Ok(settings)
+}
+
+pub fn new_context(
+    settings: &RepoSettings,
+    project_name: &str,
+    source: &TemplateSource,
+    author: &Author,
+) -> Result<TeraContext> {
+    let mut ctx = TeraContext::new();
 
     ctx.try_insert("project_name", &project_name)
         .context("failed adding value to context")?;
+    builtins::fill(&mut ctx, source)?;
 
-    let config = GitConfig::open_default()
-        .context("failed opening default git config")?
-        .snapshot()
-        .context("failed creating git config snapshot")?;
+    if let Some(name) = &author.name {
+        ctx.try_insert("git_name", name)
+            .context("failed adding value to context")?;
+    }
 
-    let name = config
-        .get_str("user.name")
-        .context("failed getting name from git config")?;
-    let email = config
-        .get_str("user.email")
-        .context("failed getting email from git config")?;
+    if let Some(email) = &author.email {
+        ctx.try_insert("git_email", email)
+            .context("failed adding value to context")?;
+    }
 
-    ctx.try_insert("git_author", &format!("{name} <{email}>"))
-        .context("failed adding value to context")?;
-    ctx.try_insert("git_name", &name)
-        .context("failed adding value to context")?;
-    ctx.try_insert("git_email", &email)
-        .context("failed adding value to context")?;
+    if let (Some(name), Some(email)) = (&author.name, &author.email) {
+        ctx.try_insert("git_author", &format!("{name} <{email}>"))
+            .context("failed adding value to context")?;
+    }
 
-    let crate_type = if let Some(ty) = settings.crate_type {
-        ty
-    } else {
-        let setting = ListSetting {
-            values: IndexSet::from_iter(["bin".to_owned(), "lib".to_owned()]),
-            default: None,
-        };
-        match prompts::prompt_list("what crate type would you like to create?", setting)?.as_ref() {
-            "bin" => CrateType::Bin,
-            "lib" => CrateType::Lib,
-            _ => unreachable!(),
+    let crate_type = match settings.crate_type.as_slice() {
+        [ty] => *ty,
+        types => {
+            let types = if types.is_empty() {
+                &[CrateType::Bin, CrateType::Lib]
+            } else {
+                types
+            };
+            let setting = ListSetting {
+                values: types
+                    .iter()
+                    .map(|ty| Choice::from(ty.as_str().to_owned()))
+                    .collect(),
+                default: None,
+            };
+            let selected = prompts::prompt_list(
+                &Prompt::new("what crate type would you like to create?"),
+                setting,
+            )?;
+
+            CrateType::ALL
+                .into_iter()
+                .find(|ty| ty.as_str() == selected)
+                .context("unknown crate type selected")?
         }
     };
 
     ctx.try_insert("crate_type", &crate_type)
         .context("failed adding value to context")?;
-    ctx.try_insert("crate_bin", &(crate_type == CrateType::Bin))
-        .context("failed adding value to context")?;
-    ctx.try_insert("crate_lib", &(crate_type == CrateType::Lib))
-        .context("failed adding value to context")?;
+
+    for (name, value) in crate_type.flags() {
+        ctx.try_insert(name, &value)
+            .context("failed adding value to context")?;
+    }
 
     Ok(ctx)
 }
 
+/// Fill the context with values for all settings, either by prompting the user or taking them
+/// from the given defaults.
+///
+/// Returns the defaults that weren't used, as the template has no setting with their name.
 pub fn fill_context<H>(
     ctx: &mut TeraContext,
-    args: IndexMap<String, RepoSetting>,
+    settings: &RepoSettings,
     mut defaults: HashMap<String, DefaultSetting, H>,
-) -> Result<()>
+) -> Result<HashMap<String, DefaultSetting, H>>
 where
     H: BuildHasher,
 {
-    for (name, setting) in args {
-        if let Some(condition) = setting.condition {
-            let result = Tera::one_off(&condition, ctx, false)?;
-            let active = result.trim().parse::<bool>()?;
+    let mut current = None;
+    let mut enabled = HashMap::new();
+
+    for (name, setting) in &settings.args {
+        let group = setting.group.as_deref();
+
+        if let Some(group) = group.filter(|group| current != Some(*group)) {
+            let enable = enter_group(ctx, group, &settings.groups[group], defaults.remove(group))?;
+            enabled.entry(group).or_insert(enable);
+        }
+
+        current = group;
+
+        if group.map_or(true, |group| enabled[group]) {
+            fill_setting(ctx, name, setting, defaults.remove(name))?;
+        } else {
+            fill_default(ctx, name, setting, defaults.remove(name))?;
+        }
+
+        fill_computed(ctx, &settings.computed, Some(name))?;
+    }
+
+    fill_computed(ctx, &settings.computed, None)?;
+
+    for name in settings.groups.keys() {
+        defaults.remove(name);
+    }
+
+    Ok(defaults)
+}
+
+/// Show the header of a group, and ask its question if it has one and the group wasn't entered
+/// before. The answer is added to the context under the group's name.
+///
+/// Returns whether the group's settings should be prompted for.
+fn enter_group(
+    ctx: &mut TeraContext,
+    name: &str,
+    group: &Group,
+    default: Option<DefaultSetting>,
+) -> Result<bool> {
+    println!("\n{}", group.title.as_str().bold());
+
+    let Some(question) = &group.question else {
+        return Ok(true);
+    };
+
+    if let Some(value) = ctx.get(name) {
+        return Ok(value.as_bool().unwrap_or_default());
+    }
+
+    let enable = run(
+        BoolSetting {
+            default: group.default,
+        },
+        &Prompt::new(question),
+        default,
+        defaults::get_bool,
+        prompts::prompt_bool,
+    )?;
+
+    ctx.try_insert(name, &enable)
+        .context("failed adding value to context")?;
+
+    Ok(enable)
+}
+
+/// Fill a setting without prompting the user, using the default value from the given default
+/// setting or else from the template. Settings without any default value are left out.
+fn fill_default(
+    ctx: &mut TeraContext,
+    name: &str,
+    setting: &RepoSetting,
+    default: Option<DefaultSetting>,
+) -> Result<()> {
+    let Some(value) = default
+        .map(|default| default.value)
+        .or_else(|| setting.default_value())
+    else {
+        return Ok(());
+    };
+
+    fill_setting(
+        ctx,
+        name,
+        setting,
+        Some(DefaultSetting {
+            value,
+            skip_prompt: true,
+        }),
+    )
+}
+
+/// Check all validation rules against the filled context. If a rule fails, the user is informed
+/// and prompted again for the settings related to the rule, until all rules pass.
+///
+/// Rules without any related settings that are currently active, or any failing rule when running
+/// without an interactive terminal, result in an error instead.
+pub fn validate_context(ctx: &mut TeraContext, settings: &RepoSettings) -> Result<()> {
+    loop {
+        let mut failed = None;
+
+        for rule in &settings.validate {
+            let valid = evaluate_condition(&rule.condition, ctx).with_context(|| {
+                format!("failed to evaluate validation rule `{}`", rule.message)
+            })?;
+
+            if !valid {
+                failed = Some(rule);
+                break;
+            }
+        }
+
+        let Some(rule) = failed else {
+            return Ok(());
+        };
+
+        let names = retry_settings(ctx, settings, rule)?;
+
+        if names.is_empty() || !prompts::is_interactive() {
+            bail!("validation failed: {}", rule.message);
+        }
+
+        println!("{} {}", "validation failed:".red().bold(), rule.message);
+
+        for name in names {
+            let setting = &settings.args[name];
+            let current = defaults::from_context(ctx, name)?;
+
+            fill_setting(ctx, name, setting, current)?;
+        }
+
+        refresh_context(ctx, settings)?;
+    }
+}
+
+/// Find the settings of a failed validation rule, that can be prompted again. Without an explicit
+/// list of settings, the ones the rule's condition refers to are used. Settings whose condition
+/// evaluates to `false`, or that belong to a disabled group, are left out, as prompting for them
+/// wouldn't change any value.
+fn retry_settings<'a>(
+    ctx: &TeraContext,
+    settings: &'a RepoSettings,
+    rule: &'a ValidationRule,
+) -> Result<Vec<&'a str>> {
+    let related = if rule.settings.is_empty() {
+        referenced_settings(settings, &rule.condition)
+    } else {
+        rule.settings.iter().map(String::as_str).collect()
+    };
+    let mut names = Vec::with_capacity(related.len());
+
+    for name in related {
+        let setting = &settings.args[name];
 
-            if !active {
+        if let Some(condition) = &setting.condition {
+            if !evaluate_condition(condition, ctx)? {
                 continue;
             }
         }
 
-        match setting.ty {
-            SettingType::Bool(value) => {
-                let value = run(
-                    value,
-                    &setting.description,
-                    defaults.remove(&name),
-                    defaults::get_bool,
-                    prompts::prompt_bool,
-                )?;
+        if group_disabled(ctx, setting) {
+            continue;
+        }
 
-                ctx.try_insert(name, &value)
-                    .context("failed adding value to context")?;
+        names.push(name);
+    }
+
+    Ok(names)
+}
+
+/// Find the settings a condition refers to, either directly or through one of the computed
+/// values it uses.
+fn referenced_settings<'a>(settings: &'a RepoSettings, condition: &str) -> Vec<&'a str> {
+    let computed = settings
+        .computed
+        .iter()
+        .filter(|(name, _)| references(condition, &[name]))
+        .map(|(_, computed)| computed.value())
+        .collect::<Vec<_>>();
+
+    settings
+        .args
+        .keys()
+        .filter(|name| {
+            references(condition, &[name])
+                || computed.iter().any(|value| references(value, &[name]))
+        })
+        .map(String::as_str)
+        .collect()
+}
+
+/// Check whether the setting belongs to a group, that the user chose to skip.
+fn group_disabled(ctx: &TeraContext, setting: &RepoSetting) -> bool {
+    setting.group.as_deref().map_or(false, |group| {
+        ctx.get(group).and_then(tera::Value::as_bool) == Some(false)
+    })
+}
+
+/// Show a summary of all collected values, and let the user change any of them before the
+/// project is generated. After each change, the conditions of all settings and the validation
+/// rules are evaluated again.
+///
+/// Templates without settings, or running without an interactive terminal, skip the review.
+pub fn review_context(ctx: &mut TeraContext, settings: &RepoSettings) -> Result<()> {
+    if settings.args.is_empty() || !prompts::is_interactive() {
+        return Ok(());
+    }
+
+    loop {
+        let Some(name) = prompts::prompt_review(review_answers(ctx, settings))? else {
+            return Ok(());
+        };
+
+        if settings.groups.contains_key(name) {
+            change_group(ctx, settings, name)?;
+        } else {
+            let setting = &settings.args[name];
+            let current = defaults::from_context(ctx, name)?;
+
+            fill_setting(ctx, name, setting, current)?;
+        }
+
+        refresh_context(ctx, settings)?;
+        validate_context(ctx, settings)?;
+    }
+}
+
+/// Collect all answers for the review summary. The answers to the questions of groups are listed
+/// right before the settings of each group.
+fn review_answers<'a>(ctx: &TeraContext, settings: &'a RepoSettings) -> Vec<prompts::Answer<'a>> {
+    let mut answers = Vec::new();
+    let mut current = None;
+
+    for (name, setting) in &settings.args {
+        let group = setting.group.as_deref();
+
+        if let Some(group) = group.filter(|group| current != Some(*group)) {
+            if let (Some(question), Some(value)) =
+                (&settings.groups[group].question, ctx.get(group))
+            {
+                answers.push(prompts::Answer {
+                    name: group,
+                    description: question,
+                    value: format_value(value),
+                });
             }
-            SettingType::String(value) => {
-                let value = run(
-                    value,
-                    &setting.description,
-                    defaults.remove(&name),
-                    defaults::get_string,
-                    prompts::prompt_string,
-                )?;
+        }
 
-                ctx.try_insert(name, &value)
-                    .context("failed adding value to context")?;
+        current = group;
+
+        if let Some(value) = ctx.get(name) {
+            answers.push(prompts::Answer {
+                name,
+                description: &setting.description,
+                value: format_value(value),
+            });
+        }
+    }
+
+    answers
+}
+
+/// Ask the question of a group again. If the answer changed, the settings of the group are
+/// prompted for, or reset to their defaults if the group is skipped now.
+fn change_group(ctx: &mut TeraContext, settings: &RepoSettings, name: &str) -> Result<()> {
+    let group = &settings.groups[name];
+    let Some(question) = &group.question else {
+        return Ok(());
+    };
+
+    let previous = ctx.get(name).and_then(tera::Value::as_bool);
+    let enable = run(
+        BoolSetting {
+            default: group.default,
+        },
+        &Prompt::new(question),
+        defaults::from_context(ctx, name)?,
+        defaults::get_bool,
+        prompts::prompt_bool,
+    )?;
+
+    if previous == Some(enable) {
+        return Ok(());
+    }
+
+    ctx.try_insert(name, &enable)
+        .context("failed adding value to context")?;
+
+    for (setting_name, setting) in &settings.args {
+        if setting.group.as_deref() != Some(name) {
+            continue;
+        }
+
+        let current = defaults::from_context(ctx, setting_name)?;
+        ctx.remove(setting_name);
+
+        if enable {
+            fill_setting(ctx, setting_name, setting, current)?;
+        } else {
+            fill_default(ctx, setting_name, setting, None)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Format a value from the context for display in the review summary.
+fn format_value(value: &tera::Value) -> String {
+    match value {
+        tera::Value::String(value) => value.clone(),
+        tera::Value::Array(values) => values
+            .iter()
+            .map(format_value)
+            .collect::<Vec<_>>()
+            .join(", "),
+        value => value.to_string(),
+    }
+}
+
+/// Evaluate the conditions of all settings again, after some values were changed. Settings that
+/// aren't available anymore are removed from the context, and settings that became available are
+/// filled. Afterwards, all computed values are updated.
+///
+/// The same applies to the options of lists. Selected options that are hidden now are removed from
+/// multi-lists, and lists with a hidden selection are filled again.
+fn refresh_context(ctx: &mut TeraContext, settings: &RepoSettings) -> Result<()> {
+    for (name, setting) in &settings.args {
+        if let Some(condition) = &setting.condition {
+            if !evaluate_condition(condition, ctx)? {
+                ctx.remove(name);
+            } else if !ctx.contains_key(name) {
+                refill_setting(ctx, name, setting, None)?;
             }
-            SettingType::Number(value) => {
-                let value = run(
-                    value,
-                    &setting.description,
-                    defaults.remove(&name),
-                    defaults::get_number,
-                    prompts::prompt_number,
-                )?;
+        }
 
-                ctx.try_insert(name, &value)
-                    .context("failed adding value to context")?;
+        refresh_choices(ctx, name, setting)?;
+        fill_computed(ctx, &settings.computed, Some(name))?;
+    }
+
+    fill_computed(ctx, &settings.computed, None)
+}
+
+/// Check the selection of a list or multi-list against the currently available options, and
+/// remove options that are hidden by their condition now.
+fn refresh_choices(ctx: &mut TeraContext, name: &str, setting: &RepoSetting) -> Result<()> {
+    match &setting.ty {
+        SettingType::List(list) => {
+            let Some(value) = ctx.get(name).and_then(tera::Value::as_str) else {
+                return Ok(());
+            };
+
+            if !active_choices(&list.values, ctx)?.contains(value) {
+                ctx.remove(name);
+                refill_setting(ctx, name, setting, None)?;
             }
-            SettingType::Float(value) => {
-                let value = run(
-                    value,
-                    &setting.description,
-                    defaults.remove(&name),
-                    defaults::get_float,
-                    prompts::prompt_number,
-                )?;
+        }
+        SettingType::MultiList(list) => {
+            let Some(values) = ctx.get(name).and_then(tera::Value::as_array) else {
+                return Ok(());
+            };
 
-                ctx.try_insert(name, &value)
-                    .context("failed adding value to context")?;
+            let active = active_choices(&list.values, ctx)?;
+            let kept = values
+                .iter()
+                .filter_map(tera::Value::as_str)
+                .filter(|value| active.contains(*value))
+                .map(ToOwned::to_owned)
+                .collect::<Vec<_>>();
+
+            if kept.len() == values.len() {
+                return Ok(());
             }
-            SettingType::List(value) => {
-                let value = run(
-                    value,
-                    &setting.description,
-                    defaults.remove(&name),
-                    defaults::get_list,
-                    prompts::prompt_list,
-                )?;
 
-                ctx.try_insert(name, &value)
+            if list.check(&kept.iter().cloned().collect()).is_ok() {
+                ctx.try_insert(name, &kept)
                     .context("failed adding value to context")?;
+            } else {
+                ctx.remove(name);
+                let current = DefaultSetting {
+                    value: DefaultValue::Array(kept),
+                    skip_prompt: false,
+                };
+                refill_setting(ctx, name, setting, Some(current))?;
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// Fill a setting that is missing from the context, either by prompting the user, or with its
+/// default value if its group is skipped.
+fn refill_setting(
+    ctx: &mut TeraContext,
+    name: &str,
+    setting: &RepoSetting,
+    default: Option<DefaultSetting>,
+) -> Result<()> {
+    if group_disabled(ctx, setting) {
+        fill_default(ctx, name, setting, None)
+    } else {
+        fill_setting(ctx, name, setting, default)
+    }
+}
+
+/// Evaluate a Tera template that is expected to result in either `true` or `false`.
+fn evaluate_condition(condition: &str, ctx: &TeraContext) -> Result<bool> {
+    let result = Tera::one_off(condition, ctx, false)?;
+    result
+        .trim()
+        .parse::<bool>()
+        .with_context(|| format!("condition did not evaluate to a boolean, but `{result}`"))
+}
+
+fn fill_setting(
+    ctx: &mut TeraContext,
+    name: &str,
+    setting: &RepoSetting,
+    default: Option<DefaultSetting>,
+) -> Result<()> {
+    if let Some(condition) = &setting.condition {
+        if !evaluate_condition(condition, ctx)? {
+            return Ok(());
+        }
+    }
+
+    match &setting.ty {
+        SettingType::Bool(value) => {
+            let value = run(
+                value.clone(),
+                &setting.prompt(),
+                default,
+                defaults::get_bool,
+                prompts::prompt_bool,
+            )?;
+
+            ctx.try_insert(name, &value)
+                .context("failed adding value to context")?;
+        }
+        SettingType::String(value) => {
+            let value = run(
+                value.clone(),
+                &setting.prompt(),
+                default,
+                defaults::get_string,
+                prompts::prompt_string,
+            )?;
+
+            ctx.try_insert(name, &value)
+                .context("failed adding value to context")?;
+        }
+        SettingType::Number(value) => {
+            let value = run(
+                value.clone(),
+                &setting.prompt(),
+                default,
+                defaults::get_number,
+                prompts::prompt_number,
+            )?;
+
+            ctx.try_insert(name, &value)
+                .context("failed adding value to context")?;
+        }
+        SettingType::Float(value) => {
+            let value = run(
+                value.clone(),
+                &setting.prompt(),
+                default,
+                defaults::get_float,
+                prompts::prompt_number,
+            )?;
+
+            ctx.try_insert(name, &value)
+                .context("failed adding value to context")?;
+        }
+        SettingType::List(value) => {
+            let mut value = value.clone();
+            value.values = active_choices(&value.values, ctx)?;
+
+            if value.values.is_empty() {
+                bail!("none of the possible values for `{name}` are available");
             }
-            SettingType::MultiList(value) => {
-                let value = run(
+
+            let value = run(
+                value,
+                &setting.prompt(),
+                default,
+                defaults::get_list,
+                prompts::prompt_list,
+            )?;
+
+            ctx.try_insert(name, &value)
+                .context("failed adding value to context")?;
+        }
+        SettingType::MultiList(value) => {
+            let mut value = value.clone();
+            value.values = active_choices(&value.values, ctx)?;
+
+            let choices = value.values.clone();
+            let selection = if value.values.is_empty() {
+                let selection = HashSet::new();
+                value.check(&selection)?;
+                selection
+            } else {
+                run(
                     value,
-                    &setting.description,
-                    defaults.remove(&name),
+                    &setting.prompt(),
+                    default,
                     defaults::get_multi_list,
                     prompts::prompt_multi_list,
-                )?;
+                )?
+            };
 
-                ctx.try_insert(name, &value)
-                    .context("failed adding value to context")?;
+            ctx.try_insert(name, &declared_order(&choices, &selection))
+                .context("failed adding value to context")?;
+        }
+        SettingType::License(value) => {
+            let value = run(
+                value.clone(),
+                &setting.prompt(),
+                default,
+                defaults::get_license,
+                prompts::prompt_license,
+            )?;
+
+            ctx.try_insert(name, &value)
+                .context("failed adding value to context")?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Filter the choices of a list, to only contain the ones without condition or whose condition
+/// evaluates to `true`.
+fn active_choices(choices: &IndexSet<Choice>, ctx: &TeraContext) -> Result<IndexSet<Choice>> {
+    let mut active = IndexSet::with_capacity(choices.len());
+
+    for choice in choices {
+        if let Some(condition) = &choice.condition {
+            let shown = evaluate_condition(condition, ctx).with_context(|| {
+                format!("failed to evaluate condition of option `{}`", choice.value)
+            })?;
+
+            if !shown {
+                continue;
             }
         }
+
+        active.insert(choice.clone());
+    }
+
+    Ok(active)
+}
+
+/// Sort the selected values of a multi-list in the order they're declared in, so the list and the
+/// indices of its elements are the same on every run.
+fn declared_order<'a>(choices: &'a IndexSet<Choice>, selection: &HashSet<String>) -> Vec<&'a str> {
+    choices
+        .iter()
+        .filter(|choice| selection.contains(&choice.value))
+        .map(|choice| choice.value.as_str())
+        .collect()
+}
+
+/// Evaluate all computed values, that are configured to run after the given setting, and add them
+/// to the context. If `after` is `None`, the values without any explicit position are evaluated.
+///
+/// Results are inserted with the type determined by [`computed_value`].
+fn fill_computed(
+    ctx: &mut TeraContext,
+    computed: &IndexMap<String, ComputedSetting>,
+    after: Option<&str>,
+) -> Result<()> {
+    for (name, setting) in computed.iter().filter(|(_, s)| s.after() == after) {
+        let result = Tera::one_off(setting.value(), ctx, false)
+            .with_context(|| format!("failed to evaluate computed value `{name}`"))?;
+        let value = computed_value(result.trim(), setting.ty())
+            .with_context(|| format!("invalid result for computed value `{name}`"))?;
+
+        ctx.try_insert(name, &value)
+            .context("failed adding value to context")?;
     }
 
     Ok(())
 }
 
+/// Convert the result of a computed value into the given type. Without an explicit type, results
+/// that are either `true` or `false` are inserted as boolean, all others as string.
+fn computed_value(result: &str, ty: Option<ComputedType>) -> Result<tera::Value> {
+    Ok(match ty {
+        None => result
+            .parse::<bool>()
+            .map_or_else(|_| result.into(), Into::into),
+        Some(ComputedType::String) => result.into(),
+        Some(ComputedType::Bool) => result
+            .parse::<bool>()
+            .with_context(|| format!("`{result}` is not a boolean"))?
+            .into(),
+        Some(ComputedType::Number) => result
+            .parse::<i64>()
+            .with_context(|| format!("`{result}` is not a number"))?
+            .into(),
+        Some(ComputedType::Float) => result
+            .parse::<f64>()
+            .ok()
+            .and_then(tera::Number::from_f64)
+            .map(tera::Value::Number)
+            .with_context(|| format!("`{result}` is not a float"))?,
+    })
+}
+
 fn run<S: Setting<R>, R>(
     mut setting: S,
-    description: &str,
+    info: &Prompt<'_>,
     default: Option<DefaultSetting>,
     load: impl Fn(DefaultSetting) -> Result<R>,
-    prompt: impl Fn(&str, S) -> Result<R>,
+    prompt: impl Fn(&Prompt<'_>, S) -> Result<R>,
 ) -> Result<R> {
     match default {
-        Some(default) if default.skip_prompt => load(default),
+        Some(default) if default.skip_prompt => {
+            let value = load(default)?;
+            setting.check(&value)?;
+            Ok(value)
+        }
         Some(default) => {
-            setting.set_default(load(default)?);
-            prompt(description, setting)
+            let value = load(default)?;
+            // Pre-selections that don't fit anymore, like options hidden by their condition, are
+            // dropped, as the user picks a new value anyway.
+            if setting.check(&value).is_ok() {
+                setting.set_default(value);
+            }
+            prompt(info, setting)
         }
-        None => prompt(description, setting),
+        None => prompt(info, setting),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn computed_values() {
+        let raw = r#"
+        [computed]
+        bin_path = "target/release/{{ project_name }}"
+        is_hatch = { value = "{{ project_name == 'hatch' }}" }
+        next_major = { value = "{{ major + 1 }}", type = "number" }
+        ratio = { value = "{{ major / 2 }}", type = "float" }
+        is_next = "{{ next_major > 1 }}"
+        zip = "007"
+        version = "{{ '1.5' }}"
+        flag = { value = "true", type = "string" }
+        "#;
+        let settings = basic_toml::from_str::<RepoSettings>(raw).unwrap();
+
+        let mut ctx = TeraContext::new();
+        ctx.insert("project_name", "hatch");
+        ctx.insert("major", &1);
+
+        fill_context(&mut ctx, &settings, HashMap::new()).unwrap();
+
+        let json = ctx.into_json();
+        assert_eq!("target/release/hatch", json["bin_path"]);
+        assert_eq!(true, json["is_hatch"]);
+        assert_eq!(2, json["next_major"]);
+        assert_eq!(Some(0.5), json["ratio"].as_f64());
+        assert_eq!(true, json["is_next"]);
+        assert_eq!("007", json["zip"]);
+        assert_eq!("1.5", json["version"]);
+        assert_eq!("true", json["flag"]);
+
+        let settings = basic_toml::from_str::<RepoSettings>(
+            r#"
+            [computed]
+            count = { value = "{{ project_name }}", type = "number" }
+            "#,
+        )
+        .unwrap();
+        let mut ctx = TeraContext::new();
+        ctx.insert("project_name", "hatch");
+        assert!(fill_context(&mut ctx, &settings, HashMap::new()).is_err());
+    }
+
+    #[test]
+    fn validation_rules() {
+        let raw = r#"
+        [[validate]]
+        condition = "{{ min <= max }}"
+        message = "minimum must not exceed the maximum"
+
+        [[validate]]
+        condition = "{{ project_name != 'hatch' }}"
+        message = "project must not be named hatch"
+        "#;
+        let settings = basic_toml::from_str::<RepoSettings>(raw).unwrap();
+
+        let mut ctx = TeraContext::new();
+        ctx.insert("project_name", "egg");
+        ctx.insert("min", &1);
+        ctx.insert("max", &5);
+        assert!(validate_context(&mut ctx, &settings).is_ok());
+
+        ctx.insert("project_name", "hatch");
+        let err = validate_context(&mut ctx, &settings).unwrap_err();
+        assert_eq!(
+            "validation failed: project must not be named hatch",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn default_precedence() {
+        let raw = r#"
+        [a]
+        type = "string"
+        description = "a"
+        [b]
+        type = "string"
+        description = "b"
+        [c]
+        type = "string"
+        description = "c"
+        [d]
+        type = "string"
+        description = "d"
+        "#;
+        let settings = basic_toml::from_str::<RepoSettings>(raw).unwrap();
+
+        let parse = |raw| basic_toml::from_str::<HashMap<String, DefaultSetting>>(raw).unwrap();
+        let bookmark = parse("a = \"1\"\nb = \"1\"");
+        let first = parse("b = \"2\"\nc = \"2\"");
+        let second = parse("c = \"3\"");
+
+        let mut remembered = Answers::new();
+        remembered.insert("a".to_owned(), "9".into());
+        remembered.insert("d".to_owned(), "9".into());
+
+        let defaults = settings
+            .merge_defaults(
+                &[
+                    (DefaultSource::Bookmark("main"), &bookmark),
+                    (DefaultSource::Preset("first"), &first),
+                    (DefaultSource::Preset("second"), &second),
+                ],
+                remembered,
+            )
+            .unwrap();
+
+        let value = |name| &defaults[name].value;
+        assert_eq!(&DefaultValue::String("1".to_owned()), value("a"));
+        assert_eq!(&DefaultValue::String("2".to_owned()), value("b"));
+        assert_eq!(&DefaultValue::String("3".to_owned()), value("c"));
+        assert_eq!(&DefaultValue::String("9".to_owned()), value("d"));
+        assert!(!defaults["a"].skip_prompt);
+
+        let invalid = parse("a = 1");
+        let err = settings
+            .merge_defaults(&[(DefaultSource::Preset("typo"), &invalid)], Answers::new())
+            .unwrap_err();
+        assert_eq!("invalid defaults in preset `typo`", err.to_string());
+    }
+
+    #[test]
+    fn inactive_rule_settings() {
+        let raw = r#"
+        [[validate]]
+        condition = "{{ port > 1024 }}"
+        message = "port must not be privileged"
+        settings = ["port", "host"]
+
+        [server]
+        type = "bool"
+        description = "server"
+
+        [port]
+        type = "number"
+        description = "port"
+        min = 0
+        max = 65535
+        condition = "{{ server }}"
+
+        [host]
+        type = "string"
+        description = "host"
+        group = "network"
+
+        [groups.network]
+        title = "network"
+        "#;
+        let settings = basic_toml::from_str::<RepoSettings>(raw).unwrap();
+        let rule = &settings.validate[0];
+
+        let mut ctx = TeraContext::new();
+        ctx.insert("server", &false);
+        ctx.insert("network", &false);
+        ctx.insert("port", &80);
+        assert!(retry_settings(&ctx, &settings, rule).unwrap().is_empty());
+
+        let err = validate_context(&mut ctx, &settings).unwrap_err();
+        assert_eq!(
+            "validation failed: port must not be privileged",
+            err.to_string()
+        );
+
+        ctx.insert("server", &true);
+        assert_eq!(vec!["port"], retry_settings(&ctx, &settings, rule).unwrap());
+
+        ctx.insert("network", &true);
+        assert_eq!(
+            vec!["port", "host"],
+            retry_settings(&ctx, &settings, rule).unwrap()
+        );
+
+        let raw = r#"
+        [[validate]]
+        condition = "{{ min_port <= max_port and not wide }}"
+        message = "ports must form a narrow range"
+
+        [computed]
+        wide = "{{ max_port - min_port > 100 }}"
+
+        [min_port]
+        type = "number"
+        description = "min"
+        min = 0
+        max = 65535
+
+        [max_port]
+        type = "number"
+        description = "max"
+        min = 0
+        max = 65535
+
+        [host]
+        type = "string"
+        description = "host"
+        "#;
+        let settings = basic_toml::from_str::<RepoSettings>(raw).unwrap();
+        assert_eq!(
+            vec!["min_port", "max_port"],
+            retry_settings(&ctx, &settings, &settings.validate[0]).unwrap()
+        );
+    }
+
+    #[test]
+    fn string_validators() {
+        let raw = r#"
+        [single]
+        type = "string"
+        description = "single"
+        validator = "crate"
+
+        [legacy_regex]
+        type = "string"
+        description = "legacy regex"
+        validator.regex = "^[a-z]+$"
+
+        [many]
+        type = "string"
+        description = "many"
+        validators = [
+            "ident",
+            { type = "regex", pattern = "^[a-z_]+$", message = "only lowercase" },
+            { type = "min_length", length = 3 },
+            { type = "max_length", length = 20 },
+            { type = "not_in", values = ["core", "std"] },
+        ]
+        "#;
+        let settings = basic_toml::from_str::<RepoSettings>(raw).unwrap();
+
+        let validators = |name: &str| match &settings.args[name].ty {
+            SettingType::String(setting) => setting.validators.clone(),
+            _ => unreachable!(),
+        };
+
+        assert!(matches!(
+            validators("single").as_slice(),
+            [Validator {
+                kind: StringValidator::Crate,
+                message: None
+            }]
+        ));
+        assert!(matches!(
+            validators("legacy_regex").as_slice(),
+            [Validator {
+                kind: StringValidator::Regex { .. },
+                message: None
+            }]
+        ));
+        assert!(matches!(
+            validators("many").as_slice(),
+            [
+                Validator {
+                    kind: StringValidator::Ident,
+                    ..
+                },
+                Validator {
+                    kind: StringValidator::Regex { .. },
+                    message: Some(_)
+                },
+                Validator {
+                    kind: StringValidator::MinLength { length: 3 },
+                    ..
+                },
+                Validator {
+                    kind: StringValidator::MaxLength { length: 20 },
+                    ..
+                },
+                Validator {
+                    kind: StringValidator::NotIn { .. },
+                    ..
+                },
+            ]
+        ));
+
+        assert!(basic_toml::from_str::<RepoSettings>(
+            r#"
+            [invalid]
+            type = "string"
+            description = "invalid"
+            validator = "min_length"
+            "#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn author_references() {
+        let names = ["git_author", "git_name", "git_email"];
+
+        let settings = basic_toml::from_str::<RepoSettings>(
+            r#"
+            [computed]
+            copyright = "{{ year }} {{ git_name }}"
+            "#,
+        )
+        .unwrap();
+        assert!(settings.references(&names));
+
+        let settings = basic_toml::from_str::<RepoSettings>(
+            r#"
+            [license]
+            type = "license"
+            description = "license"
+            "#,
+        )
+        .unwrap();
+        assert!(settings.references(&names));
+
+        let settings = basic_toml::from_str::<RepoSettings>(
+            r#"
+            [license]
+            type = "license"
+            description = "license"
+            files = false
+            "#,
+        )
+        .unwrap();
+        assert!(!settings.references(&names));
+
+        let settings = basic_toml::from_str::<RepoSettings>(
+            r#"
+            [hosting]
+            type = "list"
+            description = "hosting"
+            values = [
+                "none",
+                { value = "github", condition = "{{ git_email is ending_with('@github.com') }}" },
+            ]
+            "#,
+        )
+        .unwrap();
+        assert!(settings.references(&names));
+
+        assert!(references("{{ git_name }}", &names));
+        assert!(references("{{git_email|lower}}", &names));
+        assert!(!references("{{ my_git_name }}", &names));
+        assert!(!references("{{ author.git_author }}", &names));
+        assert!(!references("{{ git_names }}", &names));
+    }
+
+    #[test]
+    fn crate_types() {
+        let settings = basic_toml::from_str::<RepoSettings>(r#"crate_type = "none""#).unwrap();
+        assert!(settings.crate_type == [CrateType::None]);
+
+        let settings =
+            basic_toml::from_str::<RepoSettings>(r#"crate_type = ["bin+lib", "proc-macro"]"#)
+                .unwrap();
+        assert!(settings.crate_type == [CrateType::BinLib, CrateType::ProcMacro]);
+
+        assert_eq!(
+            [
+                ("crate_bin", true),
+                ("crate_lib", true),
+                ("crate_proc_macro", false),
+                ("crate_cdylib", false),
+                ("crate_staticlib", false),
+            ],
+            CrateType::BinLib.flags()
+        );
+        assert!(CrateType::None.flags().iter().all(|(_, flag)| !flag));
+    }
+
+    #[test]
+    fn list_choices() {
+        let raw = r#"
+        [database]
+        type = "list"
+        description = "database"
+        values = [
+            "none",
+            { value = "pg", label = "PostgreSQL", description = "full-featured SQL database" },
+            { value = "sqlite", label = "SQLite", condition = "{{ crate_bin }}" },
+        ]
+        default = "pg"
+        "#;
+        let settings = basic_toml::from_str::<RepoSettings>(raw).unwrap();
+        assert!(settings.args["database"].validate().is_none());
+
+        let SettingType::List(setting) = &settings.args["database"].ty else {
+            unreachable!()
+        };
+
+        let labels = setting
+            .values
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec!["none", "PostgreSQL - full-featured SQL database", "SQLite"],
+            labels
+        );
+
+        let mut ctx = TeraContext::new();
+        ctx.insert("crate_bin", &false);
+
+        let active = active_choices(&setting.values, &ctx).unwrap();
+        assert!(active.contains("pg"));
+        assert!(!active.contains("sqlite"));
+    }
+
+    #[test]
+    fn multi_list_constraints() {
+        let validate = |extra: &str| {
+            let raw = format!(
+                r#"
+                [databases]
+                type = "multi_list"
+                description = "databases"
+                values = ["pg", "sqlite", "redis"]
+                {extra}
+                "#
+            );
+            basic_toml::from_str::<RepoSettings>(&raw).unwrap().args["databases"].validate()
+        };
+
+        assert_eq!(None, validate("min = 1\nmax = 2"));
+        assert_eq!(
+            None,
+            validate("exclusive = [[\"pg\", \"sqlite\"]]\ndefault = [\"pg\", \"redis\"]")
+        );
+        assert!(validate("min = 2\nmax = 1").is_some());
+        assert!(validate("min = 4").is_some());
+        assert!(validate(r#"exclusive = [["pg", "mysql"]]"#).is_some());
+        assert!(validate("min = 1\ndefault = []").is_some());
+        assert!(
+            validate("exclusive = [[\"pg\", \"sqlite\"]]\ndefault = [\"pg\", \"sqlite\"]")
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn foreach_shadowing() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = Utf8Path::from_path(dir.path()).unwrap();
+        let load_with = |extra: &str| {
+            let raw = format!(
+                r#"
+                [[foreach]]
+                paths = ["crates/**"]
+                items = "services"
+
+                [services]
+                type = "multi_list"
+                description = "services"
+                values = ["api"]
+                {extra}
+                "#
+            );
+            fs::write(root.join(".hatch.toml"), raw).unwrap();
+            load(root).map(drop)
+        };
+
+        assert!(load_with("").is_ok());
+        assert_eq!(
+            "setting `item` is shadowed by the variable of the same name in foreach files",
+            load_with("[item]\ntype = \"bool\"\ndescription = \"item\"")
+                .unwrap_err()
+                .to_string()
+        );
+        assert!(load_with("[computed]\nindex = \"0\"").is_err());
+    }
+
+    #[test]
+    fn multi_list_defaults() {
+        let raw = r#"
+        [features]
+        type = "multi_list"
+        description = "features"
+        values = ["cli", "tracing"]
+        min = 1
+        "#;
+        let settings = basic_toml::from_str::<RepoSettings>(raw).unwrap();
+        let defaults = |raw| basic_toml::from_str::<HashMap<String, DefaultSetting>>(raw).unwrap();
+
+        let unknown = defaults(r#"features = { value = ["zzz"], skip_prompt = true }"#);
+        assert!(settings.check_defaults(&unknown).is_err());
+
+        let mut ctx = TeraContext::new();
+        let err = fill_context(&mut ctx, &settings, unknown).unwrap_err();
+        assert_eq!(
+            "`zzz` isn't one of the possible values",
+            err.root_cause().to_string()
+        );
+        assert!(ctx.get("features").is_none());
+
+        let known = defaults(r#"features = { value = ["tracing", "cli"], skip_prompt = true }"#);
+        assert!(settings.check_defaults(&known).is_ok());
+
+        let mut ctx = TeraContext::new();
+        fill_context(&mut ctx, &settings, known).unwrap();
+        assert_eq!(
+            Some(&tera::Value::from(vec!["cli", "tracing"])),
+            ctx.get("features")
+        );
+    }
+
+    #[test]
+    fn prompt_texts() {
+        let raw = r#"
+            [repository]
+            type = "string"
+            description = "repository"
+            help = "used in the Cargo.toml"
+            placeholder = "https://..."
+
+            [name]
+            type = "string"
+            description = "name"
+        "#;
+
+        let settings = basic_toml::from_str::<RepoSettings>(raw).unwrap();
+
+        let prompt = settings.args["repository"].prompt();
+        assert_eq!("repository", prompt.message);
+        assert_eq!(Some("used in the Cargo.toml"), prompt.help);
+        assert_eq!(Some("https://..."), prompt.placeholder);
+        assert_eq!(None, prompt.example);
+
+        let prompt = settings.args["name"].prompt();
+        assert_eq!(None, prompt.help);
+        assert_eq!(None, prompt.placeholder);
+    }
+
+    #[test]
+    fn skipped_groups() {
+        let raw = r#"
+        [groups.observability]
+        title = "Observability"
+        question = "Configure observability?"
+
+        [tracing]
+        type = "bool"
+        description = "tracing"
+        default = true
+        group = "observability"
+
+        [endpoint]
+        type = "string"
+        description = "endpoint"
+        group = "observability"
+
+        [level]
+        type = "list"
+        description = "level"
+        values = ["debug", "info"]
+        default = "info"
+        group = "observability"
+        "#;
+        let settings = basic_toml::from_str::<RepoSettings>(raw).unwrap();
+
+        let defaults = [
+            (
+                "observability".to_owned(),
+                DefaultSetting {
+                    value: DefaultValue::Bool(false),
+                    skip_prompt: true,
+                },
+            ),
+            (
+                "level".to_owned(),
+                DefaultSetting {
+                    value: DefaultValue::String("debug".to_owned()),
+                    skip_prompt: false,
+                },
+            ),
+        ]
+        .into_iter()
+        .collect::<HashMap<_, _>>();
+
+        let mut ctx = TeraContext::new();
+        fill_context(&mut ctx, &settings, defaults).unwrap();
+
+        assert_eq!(Some(&false.into()), ctx.get("observability"));
+        assert_eq!(Some(&true.into()), ctx.get("tracing"));
+        assert_eq!(None, ctx.get("endpoint"));
+        assert_eq!(Some(&"debug".into()), ctx.get("level"));
+    }
+
+    #[test]
+    fn refresh_conditions() {
+        let raw = r#"
+        [computed]
+        summary = "{{ database | default(value='none') }}"
+
+        [with_db]
+        type = "bool"
+        description = "with database"
+
+        [database]
+        type = "string"
+        description = "database"
+        condition = "{{ with_db }}"
+        "#;
+        let settings = basic_toml::from_str::<RepoSettings>(raw).unwrap();
+
+        let mut ctx = TeraContext::new();
+        ctx.insert("with_db", &false);
+        ctx.insert("database", "postgres");
+        ctx.insert("summary", "postgres");
+
+        refresh_context(&mut ctx, &settings).unwrap();
+
+        assert_eq!(None, ctx.get("database"));
+        assert_eq!(Some(&"none".into()), ctx.get("summary"));
+    }
+
+    #[test]
+    fn refresh_choices() {
+        let raw = r#"
+        [groups.extras]
+        title = "Extras"
+        question = "extras?"
+
+        [cloud]
+        type = "bool"
+        description = "cloud"
+
+        [features]
+        type = "multi_list"
+        description = "features"
+        values = ["cli", { value = "s3", condition = "{{ cloud }}" }]
+
+        [storage]
+        type = "list"
+        description = "storage"
+        values = ["disk", { value = "s3", condition = "{{ cloud }}" }]
+        default = "disk"
+        group = "extras"
+        "#;
+        let settings = basic_toml::from_str::<RepoSettings>(raw).unwrap();
+
+        let mut ctx = TeraContext::new();
+        ctx.insert("extras", &false);
+        ctx.insert("cloud", &false);
+        ctx.insert("features", &["cli", "s3"]);
+        ctx.insert("storage", "s3");
+
+        refresh_context(&mut ctx, &settings).unwrap();
+
+        assert_eq!(Some(&vec!["cli"].into()), ctx.get("features"));
+        assert_eq!(Some(&"disk".into()), ctx.get("storage"));
+
+        let answers = review_answers(&ctx, &settings)
+            .into_iter()
+            .map(|answer| answer.name)
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["cloud", "features", "extras", "storage"], answers);
+    }
+
+    #[test]
+    fn format_values() {
+        assert_eq!("text", format_value(&"text".into()));
+        assert_eq!("5", format_value(&5.into()));
+        assert_eq!("true", format_value(&true.into()));
+        assert_eq!("a, b", format_value(&vec!["a", "b"].into()));
+    }
+
+    #[test]
+    fn remembered_answers() {
+        let raw = r#"
+        [groups.extras]
+        title = "Extras"
+        question = "extras?"
+
+        [name]
+        type = "string"
+        description = "name"
+
+        [port]
+        type = "number"
+        description = "port"
+        min = 1
+        max = 65535
+
+        [features]
+        type = "multi_list"
+        description = "features"
+        values = ["a", "b"]
+        max = 1
+        group = "extras"
+
+        [db]
+        type = "list"
+        description = "db"
+        values = ["pg", "sqlite"]
+        "#;
+        let settings = basic_toml::from_str::<RepoSettings>(raw).unwrap();
+
+        let mut ctx = TeraContext::new();
+        ctx.insert("project_name", "egg");
+        ctx.insert("extras", &true);
+        ctx.insert("name", "hatch");
+        ctx.insert("port", &"not a number");
+        ctx.insert("features", &["a", "b"]);
+        ctx.insert("db", "mysql");
+
+        let answers = settings.answers(&ctx);
+        assert_eq!(
+            vec!["extras", "name", "port", "features", "db"],
+            answers.keys().collect::<Vec<_>>()
+        );
+
+        let defaults = settings.remembered_defaults(answers);
+        assert_eq!(2, defaults.len());
+        assert_eq!(
+            Some(&DefaultSetting {
+                value: DefaultValue::Bool(true),
+                skip_prompt: false,
+            }),
+            defaults.get("extras")
+        );
+        assert_eq!(
+            Some(&DefaultSetting {
+                value: DefaultValue::String("hatch".to_owned()),
+                skip_prompt: false,
+            }),
+            defaults.get("name")
+        );
+    }
+
+    #[test]
+    fn default_types() {
+        let raw = r#"
+        [animal]
+        type = "list"
+        description = "animal"
+        values = ["cat", "dog"]
+
+        [height]
+        type = "float"
+        description = "height"
+        min = 0.0
+        max = 300.0
+
+        [license]
+        type = "license"
+        description = "license"
+
+        [allowed_license]
+        type = "license"
+        description = "allowed license"
+        values = ["MIT", "Apache-2.0"]
+        "#;
+        let settings = basic_toml::from_str::<RepoSettings>(raw).unwrap();
+
+        let check = |name: &str, value| {
+            let defaults = [(
+                name.to_owned(),
+                DefaultSetting {
+                    value,
+                    skip_prompt: false,
+                },
+            )]
+            .into_iter()
+            .collect::<HashMap<_, _>>();
+
+            settings.check_defaults(&defaults)
+        };
+
+        assert!(check("animal", DefaultValue::String("cat".to_owned())).is_ok());
+        assert!(check("animal", DefaultValue::String("fish".to_owned())).is_err());
+        let skipped = basic_toml::from_str::<HashMap<_, _>>(
+            r#"animal = { value = "fish", skip_prompt = true }"#,
+        );
+        assert!(fill_context(&mut TeraContext::new(), &settings, skipped.unwrap()).is_err());
+        assert!(check("height", DefaultValue::Number(180)).is_ok());
+        assert!(check("unknown", DefaultValue::Bool(true)).is_ok());
+        assert!(check(
+            "license",
+            DefaultValue::String("MIT OR Apache-2.0".to_owned())
+        )
+        .is_ok());
+        assert!(check("license", DefaultValue::String("MIT OR".to_owned())).is_err());
+        assert!(check("allowed_license", DefaultValue::String("MIT".to_owned())).is_ok());
+        assert!(check("allowed_license", DefaultValue::String("ISC".to_owned())).is_err());
+        assert_eq!(
+            "invalid default value for setting `animal`",
+            check("animal", DefaultValue::Bool(true))
+                .unwrap_err()
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn unused_defaults() {
+        let raw = r#"
+        [with_tracing]
+        type = "bool"
+        description = "tracing"
+        "#;
+        let settings = basic_toml::from_str::<RepoSettings>(raw).unwrap();
+
+        let defaults = ["with_tracing", "with_tracng"]
+            .into_iter()
+            .map(|name| {
+                (
+                    name.to_owned(),
+                    DefaultSetting {
+                        value: DefaultValue::Bool(true),
+                        skip_prompt: true,
+                    },
+                )
+            })
+            .collect::<HashMap<_, _>>();
+
+        let mut ctx = TeraContext::new();
+        let unused = fill_context(&mut ctx, &settings, defaults).unwrap();
+
+        assert_eq!(vec!["with_tracng"], unused.keys().collect::<Vec<_>>());
+        assert_eq!(Some("with_tracing"), settings.suggest("with_tracng"));
+        assert_eq!(None, settings.suggest("database"));
     }
 }
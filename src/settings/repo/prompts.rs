@@ -1,17 +1,60 @@
 #![allow(clippy::needless_pass_by_value)]
 
-use std::collections::HashSet;
+use std::{
+    collections::HashSet,
+    fmt::{self, Display},
+    io,
+};
 
 use anyhow::Result;
-use crossterm::style::Stylize;
+use crossterm::{style::Stylize, tty::IsTty};
 use inquire::{Confirm, CustomType, MultiSelect, Select, Text};
 
 use super::{
-    validators, BoolSetting, ListSetting, MultiListSetting, Number, NumberSetting, StringSetting,
-    StringValidator,
+    validators, BoolSetting, LicenseSetting, ListSetting, MultiListSetting, Number, NumberSetting,
+    StringSetting, StringValidator, Validator,
 };
 
-pub fn prompt_bool(description: &str, setting: BoolSetting) -> Result<bool> {
+/// Texts, that are shown to the user when prompting for a single setting.
+#[derive(Clone, Copy)]
+pub struct Prompt<'a> {
+    /// Main message, that describes the value being asked for.
+    pub message: &'a str,
+    /// Longer explanation, shown below the prompt.
+    pub help: Option<&'a str>,
+    /// Hint, shown in the input field as long as it is empty.
+    pub placeholder: Option<&'a str>,
+    /// Example value, shown together with the help text.
+    pub example: Option<&'a str>,
+}
+
+impl<'a> Prompt<'a> {
+    pub fn new(message: &'a str) -> Self {
+        Self {
+            message,
+            help: None,
+            placeholder: None,
+            example: None,
+        }
+    }
+
+    /// Combine the help text and the example into a single message, if any of them is set.
+    fn help_message(&self) -> Option<String> {
+        match (self.help, self.example) {
+            (Some(help), Some(example)) => Some(format!("{help} (e.g. {example})")),
+            (Some(help), None) => Some(help.to_owned()),
+            (None, Some(example)) => Some(format!("e.g. {example}")),
+            (None, None) => None,
+        }
+    }
+}
+
+/// Whether the standard input is connected to a terminal, and the user can be prompted for input.
+pub fn is_interactive() -> bool {
+    io::stdin().is_tty()
+}
+
+pub fn prompt_bool(info: &Prompt<'_>, setting: BoolSetting) -> Result<bool> {
     fn default_value_formatter(value: bool) -> String {
         if value {
             format!("{}/n", "Y".green())
@@ -20,31 +63,62 @@ pub fn prompt_bool(description: &str, setting: BoolSetting) -> Result<bool> {
         }
     }
 
+    let help_message = info.help_message();
     let mut prompt =
-        Confirm::new(description).with_default_value_formatter(&default_value_formatter);
+        Confirm::new(info.message).with_default_value_formatter(&default_value_formatter);
     prompt.default = setting.default;
+    prompt.placeholder = info.placeholder;
+    prompt.help_message = help_message.as_deref();
 
     prompt.prompt().map_err(Into::into)
 }
 
-#[allow(clippy::type_complexity)]
-pub fn prompt_string(description: &str, setting: StringSetting) -> Result<String> {
-    let mut prompt = Text::new(description);
+pub fn prompt_string(info: &Prompt<'_>, setting: StringSetting) -> Result<String> {
+    let help_message = info.help_message();
+    let mut prompt = Text::new(info.message);
     prompt.default = setting.default.as_deref();
+    prompt.placeholder = info.placeholder;
+    prompt.help_message = help_message.as_deref();
 
-    let prompt = match setting.validator {
-        None => prompt.with_validator(validators::Required),
-        Some(StringValidator::Crate) => prompt.with_validator(validators::Krate),
-        Some(StringValidator::Ident) => prompt.with_validator(validators::Ident),
-        Some(StringValidator::Semver) => prompt.with_validator(validators::Semver),
-        Some(StringValidator::SemverReq) => prompt.with_validator(validators::SemverReq),
-        Some(StringValidator::Regex(re)) => prompt.with_validator(validators::Regex(re)),
+    let prompt = if setting.validators.is_empty() {
+        prompt.with_validator(validators::Required)
+    } else {
+        let validators = setting
+            .validators
+            .into_iter()
+            .map(create_validator)
+            .collect::<Vec<_>>();
+        prompt.with_validators(&validators)
     };
 
     prompt.prompt().map_err(Into::into)
 }
 
-pub fn prompt_number<T: Number>(description: &str, setting: NumberSetting<T>) -> Result<T> {
+fn create_validator(validator: Validator) -> Box<dyn inquire::validator::StringValidator> {
+    let inner: Box<dyn inquire::validator::StringValidator> = match validator.kind {
+        StringValidator::Crate => Box::new(validators::Krate),
+        StringValidator::Ident => Box::new(validators::Ident),
+        StringValidator::Semver => Box::new(validators::Semver),
+        StringValidator::SemverReq => Box::new(validators::SemverReq),
+        StringValidator::Email => Box::new(validators::Email),
+        StringValidator::Url => Box::new(validators::Url),
+        StringValidator::Spdx => Box::new(validators::Spdx),
+        StringValidator::Regex { pattern } => Box::new(validators::Regex(pattern)),
+        StringValidator::MinLength { length } => Box::new(validators::MinLength(length)),
+        StringValidator::MaxLength { length } => Box::new(validators::MaxLength(length)),
+        StringValidator::NotIn { values } => Box::new(validators::NotIn(values)),
+    };
+
+    match validator.message {
+        Some(message) => Box::new(validators::Message {
+            validator: inner,
+            message,
+        }),
+        None => inner,
+    }
+}
+
+pub fn prompt_number<T: Number>(info: &Prompt<'_>, setting: NumberSetting<T>) -> Result<T> {
     fn parser<T: Number>(value: &str, min: T, max: T) -> Result<T, ()> {
         match value.parse() {
             Ok(v) if (min..=max).contains(&v) => Ok(v),
@@ -54,11 +128,13 @@ pub fn prompt_number<T: Number>(description: &str, setting: NumberSetting<T>) ->
 
     let parser = |value: &str| parser(value, setting.min, setting.max);
     let placeholder = format!("{}..={}", setting.min, setting.max);
-    let help_message = format!("Number in range {}..={}", setting.min, setting.max);
+    let help_message = info
+        .help_message()
+        .unwrap_or_else(|| format!("Number in range {}..={}", setting.min, setting.max));
 
-    let mut prompt = CustomType::<T>::new(description)
+    let mut prompt = CustomType::<T>::new(info.message)
         .with_parser(&parser)
-        .with_placeholder(&placeholder)
+        .with_placeholder(info.placeholder.unwrap_or(&placeholder))
         .with_help_message(&help_message)
         .with_error_message("Please type a valid number within range.");
 
@@ -67,31 +143,39 @@ pub fn prompt_number<T: Number>(description: &str, setting: NumberSetting<T>) ->
     prompt.prompt().map_err(Into::into)
 }
 
-pub fn prompt_list(description: &str, setting: ListSetting) -> Result<String> {
+pub fn prompt_list(info: &Prompt<'_>, setting: ListSetting) -> Result<String> {
     let default = setting
-        .values
-        .iter()
-        .position(|v| Some(v) == setting.default.as_ref())
+        .default
+        .as_deref()
+        .and_then(|default| setting.values.get_index_of(default))
         .unwrap_or_default();
 
-    let prompt = Select::new(description, setting.values.into_iter().collect())
+    let help_message = info.help_message();
+    let mut prompt = Select::new(info.message, setting.values.into_iter().collect())
         .with_starting_cursor(default);
 
-    prompt.prompt().map_err(Into::into)
+    if let Some(help_message) = &help_message {
+        prompt.help_message = Some(help_message);
+    }
+
+    prompt
+        .prompt()
+        .map(|choice| choice.value)
+        .map_err(Into::into)
 }
 
-pub fn prompt_multi_list(description: &str, setting: MultiListSetting) -> Result<HashSet<String>> {
+pub fn prompt_multi_list(info: &Prompt<'_>, setting: MultiListSetting) -> Result<HashSet<String>> {
     let (index, selection) = if let Some(default) = setting.default.as_ref() {
         let index = setting
             .values
             .iter()
-            .position(|value| default.contains(value))
+            .position(|choice| default.contains(&choice.value))
             .unwrap_or_default();
         let selection = setting
             .values
             .iter()
             .enumerate()
-            .filter_map(|(i, value)| default.contains(value).then_some(i))
+            .filter_map(|(i, choice)| default.contains(&choice.value).then_some(i))
             .collect();
 
         (index, selection)
@@ -99,12 +183,93 @@ pub fn prompt_multi_list(description: &str, setting: MultiListSetting) -> Result
         (0, Vec::new())
     };
 
-    let prompt = MultiSelect::new(description, setting.values.into_iter().collect())
+    let validator = setting.selection();
+    let help_message = info.help_message();
+    let mut prompt = MultiSelect::new(info.message, setting.values.into_iter().collect())
         .with_starting_cursor(index)
-        .with_default(&selection);
+        .with_default(&selection)
+        .with_validator(validator);
+
+    if let Some(help_message) = &help_message {
+        prompt.help_message = Some(help_message);
+    }
 
     prompt
         .prompt()
-        .map(|v| v.into_iter().collect())
+        .map(|v| v.into_iter().map(|choice| choice.value).collect())
         .map_err(Into::into)
 }
+
+pub fn prompt_license(info: &Prompt<'_>, setting: LicenseSetting) -> Result<String> {
+    if setting.values.is_empty() {
+        let help_message = info.help_message();
+        let mut prompt = Text::new(info.message).with_validator(validators::Spdx);
+        prompt.default = setting.default.as_deref();
+        prompt.placeholder = info.placeholder;
+        prompt.help_message = help_message.as_deref();
+
+        prompt.prompt().map_err(Into::into)
+    } else {
+        prompt_list(
+            info,
+            ListSetting {
+                values: setting.values.into_iter().map(Into::into).collect(),
+                default: setting.default,
+            },
+        )
+    }
+}
+
+/// A single collected value, as shown in the review summary.
+pub struct Answer<'a> {
+    pub name: &'a str,
+    pub description: &'a str,
+    pub value: String,
+}
+
+enum ReviewOption<'a> {
+    Confirm,
+    Change(Answer<'a>),
+}
+
+impl Display for ReviewOption<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Confirm => f.write_str("yes, generate the project"),
+            Self::Change(answer) => write!(f, "no, change: {}", answer.description),
+        }
+    }
+}
+
+/// Print a summary of all answers, and let the user pick one to change. Returns the name of the
+/// picked setting, or `None` once the user confirmed the answers.
+pub fn prompt_review(answers: Vec<Answer<'_>>) -> Result<Option<&str>> {
+    let width = answers
+        .iter()
+        .map(|answer| answer.description.chars().count())
+        .max()
+        .unwrap_or_default();
+
+    println!("\n{}", "Summary".bold());
+
+    for answer in &answers {
+        println!(
+            "  {:width$}  {}",
+            answer.description,
+            answer.value.as_str().cyan()
+        );
+    }
+
+    println!();
+
+    let options = std::iter::once(ReviewOption::Confirm)
+        .chain(answers.into_iter().map(ReviewOption::Change))
+        .collect();
+
+    let selected = Select::new("generate the project with these values?", options).prompt()?;
+
+    Ok(match selected {
+        ReviewOption::Confirm => None,
+        ReviewOption::Change(answer) => Some(answer.name),
+    })
+}
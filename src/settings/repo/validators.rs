@@ -1,7 +1,10 @@
 //! Validators that ensure additional restrictions on input data.
 
+use std::borrow::Borrow;
+
 use inquire::{
-    validator::{StringValidator, Validation},
+    list_option::ListOption,
+    validator::{MultiOptionValidator, StringValidator, Validation},
     CustomUserError,
 };
 
@@ -59,7 +62,7 @@ impl StringValidator for Ident {
         }
 
         fn is_escaped_ident(input: &str) -> bool {
-            input.chars().next().map_or(false, |c| c == '_')
+            input.starts_with('_')
                 && input.chars().take(2).count() >= 2
                 && input.chars().skip(1).all(unicode_ident::is_xid_continue)
         }
@@ -118,8 +121,180 @@ impl StringValidator for Regex {
     }
 }
 
+// Validate that a value has at least the given amount of characters.
+#[derive(Clone)]
+pub struct MinLength(pub usize);
+
+impl StringValidator for MinLength {
+    fn validate(&self, input: &str) -> Result<Validation, CustomUserError> {
+        Ok(if input.chars().count() >= self.0 {
+            Validation::Valid
+        } else {
+            Validation::Invalid(format!("value must be at least {} characters long", self.0).into())
+        })
+    }
+}
+
+// Validate that a value has at most the given amount of characters.
+#[derive(Clone)]
+pub struct MaxLength(pub usize);
+
+impl StringValidator for MaxLength {
+    fn validate(&self, input: &str) -> Result<Validation, CustomUserError> {
+        Ok(if input.chars().count() <= self.0 {
+            Validation::Valid
+        } else {
+            Validation::Invalid(format!("value must be at most {} characters long", self.0).into())
+        })
+    }
+}
+
+// Validate that a value looks like an email address. This is only a rough check for the general
+// `local@domain.tld` form, not a full validation according to RFC 5322.
+#[derive(Clone)]
+pub struct Email;
+
+impl StringValidator for Email {
+    fn validate(&self, input: &str) -> Result<Validation, CustomUserError> {
+        fn is_email(input: &str) -> bool {
+            let Some((local, domain)) = input.split_once('@') else {
+                return false;
+            };
+
+            !local.is_empty()
+                && !input.chars().any(char::is_whitespace)
+                && !domain.contains('@')
+                && domain.contains('.')
+                && domain.split('.').all(|part| !part.is_empty())
+        }
+
+        Ok(if is_email(input) {
+            Validation::Valid
+        } else {
+            Validation::Invalid("value must be a valid email address".into())
+        })
+    }
+}
+
+// Validate that a value is an absolute URL.
+#[derive(Clone)]
+pub struct Url;
+
+impl StringValidator for Url {
+    fn validate(&self, input: &str) -> Result<Validation, CustomUserError> {
+        Ok(match url::Url::parse(input) {
+            Ok(_) => Validation::Valid,
+            Err(e) => Validation::Invalid(format!("value is not a valid URL: {e}").into()),
+        })
+    }
+}
+
+// Validate that a value is a SPDX license expression.
+#[derive(Clone)]
+pub struct Spdx;
+
+impl StringValidator for Spdx {
+    fn validate(&self, input: &str) -> Result<Validation, CustomUserError> {
+        Ok(match spdx::Expression::parse(input) {
+            Ok(_) => Validation::Valid,
+            Err(e) => Validation::Invalid(
+                format!("value is not a valid SPDX license expression: {}", e.reason).into(),
+            ),
+        })
+    }
+}
+
+// Validate that a value is none of the given values.
+#[derive(Clone)]
+pub struct NotIn(pub Vec<String>);
+
+impl StringValidator for NotIn {
+    fn validate(&self, input: &str) -> Result<Validation, CustomUserError> {
+        Ok(if self.0.iter().any(|value| value == input) {
+            Validation::Invalid(format!("value must not be `{input}`").into())
+        } else {
+            Validation::Valid
+        })
+    }
+}
+
+// Replace the error message of another validator with a custom one.
+#[derive(Clone)]
+pub struct Message {
+    pub validator: Box<dyn StringValidator>,
+    pub message: String,
+}
+
+impl StringValidator for Message {
+    fn validate(&self, input: &str) -> Result<Validation, CustomUserError> {
+        Ok(match self.validator.validate(input)? {
+            Validation::Valid => Validation::Valid,
+            Validation::Invalid(_) => Validation::Invalid(self.message.clone().into()),
+        })
+    }
+}
+
+// Validate the selection of a multi-list, to contain a certain amount of options and not more than
+// one option from each group of mutually exclusive options.
+#[derive(Clone)]
+pub struct Selection {
+    pub min: Option<usize>,
+    pub max: Option<usize>,
+    pub exclusive: Vec<Vec<String>>,
+}
+
+impl Selection {
+    /// Check the selected values and describe the first violated constraint, if any.
+    #[must_use]
+    pub fn check(&self, selection: &[&str]) -> Option<String> {
+        let count = selection.len();
+
+        if let Some(min) = self.min.filter(|min| count < *min) {
+            return Some(format!("select at least {min} options"));
+        }
+
+        if let Some(max) = self.max.filter(|max| count > *max) {
+            return Some(format!("select at most {max} options"));
+        }
+
+        self.exclusive.iter().find_map(|group| {
+            (selection
+                .iter()
+                .filter(|value| group.iter().any(|g| g == *value))
+                .count()
+                > 1)
+            .then(|| {
+                let group = group
+                    .iter()
+                    .map(|value| format!("`{value}`"))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!("only one of {group} can be selected")
+            })
+        })
+    }
+}
+
+impl<T: Borrow<str>> MultiOptionValidator<T> for Selection {
+    fn validate(&self, input: &[ListOption<&T>]) -> Result<Validation, CustomUserError> {
+        Ok(
+            match self.check(
+                &input
+                    .iter()
+                    .map(|option| option.value.borrow())
+                    .collect::<Vec<_>>(),
+            ) {
+                Some(message) => Validation::Invalid(message.into()),
+                None => Validation::Valid,
+            },
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use inquire::validator::ErrorMessage;
+
     use super::*;
 
     #[allow(clippy::needless_pass_by_value)]
@@ -189,4 +364,99 @@ mod tests {
         assert!(invalid(Regex("^[a-z]+$".parse().unwrap()).validate("1")));
         assert!(invalid(Regex("^[a-z]+$".parse().unwrap()).validate("")));
     }
+
+    #[test]
+    fn validate_min_length() {
+        assert!(valid(MinLength(3).validate("abc")));
+        assert!(valid(MinLength(3).validate("äöü")));
+        assert!(valid(MinLength(0).validate("")));
+        assert!(invalid(MinLength(3).validate("ab")));
+        assert!(invalid(MinLength(3).validate("")));
+    }
+
+    #[test]
+    fn validate_max_length() {
+        assert!(valid(MaxLength(3).validate("abc")));
+        assert!(valid(MaxLength(3).validate("äöü")));
+        assert!(valid(MaxLength(3).validate("")));
+        assert!(invalid(MaxLength(3).validate("abcd")));
+    }
+
+    #[test]
+    fn validate_email() {
+        assert!(valid(Email.validate("me@example.com")));
+        assert!(valid(Email.validate("first.last+tag@mail.example.com")));
+        assert!(invalid(Email.validate("me@example")));
+        assert!(invalid(Email.validate("me@example.")));
+        assert!(invalid(Email.validate("@example.com")));
+        assert!(invalid(Email.validate("me@@example.com")));
+        assert!(invalid(Email.validate("me @example.com")));
+        assert!(invalid(Email.validate("")));
+    }
+
+    #[test]
+    fn validate_url() {
+        assert!(valid(Url.validate("https://example.com")));
+        assert!(valid(
+            Url.validate("ssh://git@github.com/dnaka91/cargo-hatch.git")
+        ));
+        assert!(invalid(Url.validate("example.com")));
+        assert!(invalid(Url.validate("")));
+    }
+
+    #[test]
+    fn validate_spdx() {
+        assert!(valid(Spdx.validate("MIT")));
+        assert!(valid(Spdx.validate("MIT OR Apache-2.0")));
+        assert!(valid(
+            Spdx.validate("GPL-3.0-or-later WITH Classpath-exception-2.0")
+        ));
+        assert!(invalid(Spdx.validate("MIT OR")));
+        assert!(invalid(Spdx.validate("NOT-A-LICENSE")));
+        assert!(invalid(Spdx.validate("")));
+    }
+
+    #[test]
+    fn validate_not_in() {
+        let validator = NotIn(vec!["core".to_owned(), "std".to_owned()]);
+        assert!(valid(validator.validate("hatch")));
+        assert!(valid(validator.validate("")));
+        assert!(invalid(validator.validate("core")));
+        assert!(invalid(validator.validate("std")));
+    }
+
+    #[test]
+    fn validate_message() {
+        let validator = Message {
+            validator: Box::new(MinLength(3)),
+            message: "too short".to_owned(),
+        };
+        assert!(valid(validator.validate("abc")));
+        assert!(matches!(
+            validator.validate("ab"),
+            Ok(Validation::Invalid(ErrorMessage::Custom(message))) if message == "too short"
+        ));
+    }
+
+    #[test]
+    fn validate_selection() {
+        let validator = Selection {
+            min: Some(1),
+            max: Some(2),
+            exclusive: vec![vec!["pg".to_owned(), "sqlite".to_owned()]],
+        };
+        let check = |values: &[&str]| validator.check(values);
+
+        assert_eq!(None, check(&["pg"]));
+        assert_eq!(None, check(&["pg", "redis"]));
+        assert_eq!(Some("select at least 1 options".to_owned()), check(&[]));
+        assert_eq!(
+            Some("select at most 2 options".to_owned()),
+            check(&["pg", "redis", "kafka"])
+        );
+        assert_eq!(
+            Some("only one of `pg`, `sqlite` can be selected".to_owned()),
+            check(&["pg", "sqlite"])
+        );
+    }
 }
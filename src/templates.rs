@@ -2,31 +2,71 @@
 //! them to a target directory.
 //!
 //! Rendering means, that the file is processed through the [`Tera`] templating engine, in case it
-//! is considered a template file.
+//! is considered a template file. Symbolic links are re-created in the target directory, with their
+//! link target processed through the engine as well.
 
 use std::{
-    fs::{self, File},
-    io::{BufWriter, Write},
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    fs, io,
 };
 
-use anyhow::{Context, Result};
-use camino::{Utf8Path, Utf8PathBuf};
-use globset::{GlobBuilder, GlobSetBuilder};
+use anyhow::{bail, ensure, Context, Result};
+use camino::{Utf8Component, Utf8Path, Utf8PathBuf};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
 use ignore::WalkBuilder;
+use indexmap::IndexMap;
 use mime_guess::mime;
+use serde::Deserialize;
 use tera::{Context as TeraContext, Tera};
 
-use crate::settings::IgnorePattern;
+use crate::settings::{self, Delimiters, FileMode, ForeachRule, IgnorePattern, RepoSettings};
 
 /// A single file from a template repository, that shall be rendered into a target directory. If it
 /// is considered a template, it's processed through the [`Tera`] engine.
+#[derive(Clone)]
 pub struct RepoFile {
     /// Full path to the file for reading.
     path: Utf8PathBuf,
     /// Relative path in regards to the directory it came from.
     name: Utf8PathBuf,
-    /// Whether the file is considered a template. If not, it's copied over instead.
-    template: bool,
+    /// Relative path in the generated project, which differs from the `name` if the file was
+    /// renamed through its front matter.
+    output: Utf8PathBuf,
+    /// How the file is processed during rendering.
+    kind: FileKind,
+    /// Index and value of the list element, if the file is generated once per element of a list
+    /// through a [`ForeachRule`].
+    item: Option<(usize, tera::Value)>,
+}
+
+/// The different kinds of files, that are processed differently during rendering.
+#[derive(Clone)]
+enum FileKind {
+    /// Text file, that is processed through the [`Tera`] engine, with the settings from its front
+    /// matter.
+    Template(FrontMatter),
+    /// Binary file, that is copied over as-is.
+    Binary,
+    /// Symbolic link with the contained link target. The target is processed through the
+    /// [`Tera`] engine, and the link re-created.
+    Symlink(Utf8PathBuf),
+    /// Empty directory, that is created as-is. Directories with content are created implicitly
+    /// and not tracked separately.
+    Directory,
+}
+
+/// Settings for a single template file, defined in a `{# hatch: ... #}` comment at the very start
+/// of the file. The content of the comment is a TOML document.
+#[derive(Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FrontMatter {
+    /// Alternate delimiters for this file.
+    delimiters: Option<Delimiters>,
+    /// Tera template that decides whether the file is generated at all.
+    condition: Option<String>,
+    /// Tera template for a new path of the file, relative to its directory.
+    rename: Option<String>,
 }
 
 impl RepoFile {
@@ -35,13 +75,47 @@ impl RepoFile {
     pub fn name(&self) -> &Utf8Path {
         &self.name
     }
+
+    /// Path of this file in the generated project, relative to its root directory.
+    #[must_use]
+    pub fn output(&self) -> &Utf8Path {
+        &self.output
+    }
+
+    /// Context for rendering this file, which extends the given context with the `item` and `index`
+    /// values if the file is generated once per list element.
+    fn context<'a>(&self, context: &'a TeraContext) -> Cow<'a, TeraContext> {
+        match &self.item {
+            Some((index, item)) => {
+                let mut context = context.clone();
+                context.insert("item", item);
+                context.insert("index", index);
+                Cow::Owned(context)
+            }
+            None => Cow::Borrowed(context),
+        }
+    }
 }
 
-pub fn collect_files(dir: &Utf8Path) -> Result<Vec<RepoFile>> {
+/// Collect all files from the given directory, that are part of the template.
+///
+/// Files matching any of the `raw` glob patterns are always copied as-is, and files matching any of
+/// the `template` patterns are always rendered, taking precedence over `raw`. All other files are
+/// classified automatically by [`is_binary`].
+pub fn collect_files(dir: &Utf8Path, settings: &RepoSettings) -> Result<Vec<RepoFile>> {
     // Builtin filters for files or dirs that are always ignored
     static FILTERS: &[&str] = &[".git", ".hatch.toml", ".hatchignore"];
 
+    let raw = build_glob_set(&settings.raw)?;
+    let template = build_glob_set(&settings.template)?;
+    let delimiters = settings
+        .delimiters
+        .iter()
+        .map(|rule| Ok((build_glob_set(&rule.paths)?, &rule.delimiters)))
+        .collect::<Result<Vec<_>>>()?;
+
     let mut files = Vec::new();
+    let mut parents = HashSet::new();
     let walk = WalkBuilder::new(dir)
         .standard_filters(false)
         .git_ignore(true)
@@ -58,54 +132,277 @@ pub fn collect_files(dir: &Utf8Path) -> Result<Vec<RepoFile>> {
     for entry in walk {
         let entry = entry?;
 
-        if entry.file_type().map_or(false, |ty| ty.is_file()) {
+        let Some(ty) = entry.file_type() else {
+            continue;
+        };
+
+        if entry.depth() == 0 {
+            continue;
+        }
+
+        if ty.is_file() || ty.is_symlink() || ty.is_dir() {
             let path = entry.path();
             let path = Utf8Path::from_path(path)
-                .with_context(|| format!("{path:?} is not a valid UTF8 path"))?;
+                .with_context(|| format!("{} is not a valid UTF8 path", path.display()))?;
             let name = path
                 .strip_prefix(dir)
                 .with_context(|| format!("failed to get relative path for {path}"))?;
 
+            if let Some(parent) = name.parent() {
+                parents.insert(parent.to_owned());
+            }
+
+            let kind = if ty.is_dir() {
+                FileKind::Directory
+            } else if ty.is_symlink() {
+                let link = path
+                    .read_link_utf8()
+                    .with_context(|| format!("failed to read symlink `{name}`"))?;
+                ensure!(
+                    resolve_link(name, &link).is_some(),
+                    "symlink `{name}` points outside of the template"
+                );
+
+                FileKind::Symlink(link)
+            } else if !template.is_match(name) && (raw.is_match(name) || is_binary(path, name)?) {
+                FileKind::Binary
+            } else {
+                let content = fs::read_to_string(path)
+                    .with_context(|| format!("failed to read template `{name}`"))?;
+                let (mut front, _) = split_front_matter(&content)
+                    .with_context(|| format!("invalid front matter in `{name}`"))?;
+
+                if front.delimiters.is_none() {
+                    front.delimiters = delimiters
+                        .iter()
+                        .rev()
+                        .find(|(glob, _)| glob.is_match(name))
+                        .map(|(_, delimiters)| (*delimiters).clone());
+                }
+
+                FileKind::Template(front)
+            };
+
             files.push(RepoFile {
                 path: path.to_owned(),
                 name: name.to_owned(),
-                template: !is_binary(name),
+                output: name.to_owned(),
+                kind,
+                item: None,
             });
         }
     }
 
+    // Only keep directories without any content, as all others are created for their files.
+    files.retain(|file| !matches!(file.kind, FileKind::Directory) || !parents.contains(&file.name));
+
     Ok(files)
 }
 
-/// Determine, whether the given path is considered a binary file, that should not be treated as
+/// Split the optional front matter from the start of a template, returning its settings and the
+/// remaining content.
+///
+/// The line break after the front matter is removed as well, or all following whitespace if the
+/// comment is closed with `-#}`.
+fn split_front_matter(content: &str) -> Result<(FrontMatter, &str)> {
+    let front = content
+        .strip_prefix("{#")
+        .map(|rest| rest.strip_prefix('-').unwrap_or(rest).trim_start())
+        .and_then(|rest| rest.strip_prefix("hatch:"));
+
+    let Some(rest) = front else {
+        return Ok((FrontMatter::default(), content));
+    };
+
+    let end = rest.find("#}").context("front matter is not closed")?;
+    let (front, trim) = match rest[..end].strip_suffix('-') {
+        Some(front) => (front, true),
+        None => (&rest[..end], false),
+    };
+
+    let front = basic_toml::from_str::<FrontMatter>(front)?;
+
+    if let Some(error) = front.delimiters.as_ref().and_then(Delimiters::validate) {
+        bail!("invalid delimiters: {error}");
+    }
+
+    let rest = &rest[end + 2..];
+    let rest = if trim {
+        rest.trim_start()
+    } else {
+        rest.strip_prefix("\r\n")
+            .or_else(|| rest.strip_prefix('\n'))
+            .unwrap_or(rest)
+    };
+
+    Ok((front, rest))
+}
+
+/// Translate a template with alternate delimiters into the regular Tera syntax.
+///
+/// Content within alternate delimiters is wrapped in the regular ones instead, while the regular
+/// delimiters of overridden kinds are escaped, so they end up literally in the output.
+fn convert_delimiters(content: &str, delimiters: &Delimiters) -> Result<String> {
+    let kinds = [
+        (&delimiters.variable, "{{", "}}"),
+        (&delimiters.block, "{%", "%}"),
+        (&delimiters.comment, "{#", "#}"),
+    ];
+
+    let mut output = String::with_capacity(content.len());
+    let mut rest = content;
+
+    'outer: while !rest.is_empty() {
+        for (custom, start, end) in &kinds {
+            let Some((custom_start, custom_end)) = custom else {
+                continue;
+            };
+
+            if let Some(tail) = rest.strip_prefix(custom_start.as_str()) {
+                let pos = tail
+                    .find(custom_end.as_str())
+                    .with_context(|| format!("`{custom_start}` is never closed"))?;
+
+                output.push_str(start);
+                output.push_str(&tail[..pos]);
+                output.push_str(end);
+                rest = &tail[pos + custom_end.len()..];
+                continue 'outer;
+            }
+        }
+
+        for (custom, start, _) in &kinds {
+            if custom.is_some() {
+                if let Some(tail) = rest.strip_prefix(start) {
+                    output.push_str("{{ \"");
+                    output.push_str(start);
+                    output.push_str("\" }}");
+                    rest = tail;
+                    continue 'outer;
+                }
+            }
+        }
+
+        let Some(c) = rest.chars().next() else {
+            break;
+        };
+
+        output.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+
+    Ok(output)
+}
+
+/// Build a glob set from the given patterns, with the same matching rules as the ignore patterns.
+fn build_glob_set(patterns: &[String]) -> Result<GlobSet> {
+    let mut set = GlobSetBuilder::new();
+
+    for pattern in patterns {
+        set.add(
+            GlobBuilder::new(pattern)
+                .literal_separator(true)
+                .build()
+                .with_context(|| format!("invalid glob pattern `{pattern}`"))?,
+        );
+    }
+
+    set.build().context("failed to build the glob set")
+}
+
+/// Determine, whether the given file is considered a binary file, that should not be treated as
 /// template in further processing.
-fn is_binary(path: &Utf8Path) -> bool {
-    let mime = mime_guess::from_path(path).first_or_text_plain();
+///
+/// The file extension is checked first, and if it doesn't point to a binary format, the content is
+/// inspected for NUL bytes or invalid UTF-8, which can't be processed as template.
+fn is_binary(path: &Utf8Path, name: &Utf8Path) -> Result<bool> {
+    let mime = mime_guess::from_path(name).first_or_text_plain();
 
-    match mime.type_() {
+    let binary = match mime.type_() {
         mime::AUDIO | mime::FONT | mime::IMAGE | mime::VIDEO => true,
         mime::APPLICATION => matches!(mime.subtype(), mime::OCTET_STREAM | mime::PDF),
         _ => false,
+    };
+
+    if binary {
+        return Ok(true);
     }
+
+    let content = fs::read(path).with_context(|| format!("failed to read file `{name}`"))?;
+
+    Ok(content.contains(&0) || std::str::from_utf8(&content).is_err())
+}
+
+/// Resolve the target of a symlink, relative to the root directory that contains the link. The
+/// resolution is purely lexical, without accessing the file system.
+///
+/// Returns `None` if the link is absolute, or points outside of the root directory.
+fn resolve_link(name: &Utf8Path, link: &Utf8Path) -> Option<Utf8PathBuf> {
+    if link.has_root() {
+        return None;
+    }
+
+    let mut resolved = name.parent().unwrap_or(Utf8Path::new("")).to_owned();
+
+    for component in link.components() {
+        match component {
+            Utf8Component::Normal(part) => resolved.push(part),
+            Utf8Component::ParentDir => {
+                if !resolved.pop() {
+                    return None;
+                }
+            }
+            Utf8Component::CurDir => {}
+            Utf8Component::Prefix(_) | Utf8Component::RootDir => return None,
+        }
+    }
+
+    Some(resolved)
 }
 
-/// Filter out the collected files from [`collect_files`] with the given ignore rules.
+/// Check whether any of the template files mentions one of the given variable names. This includes
+/// the file paths and symlink targets, that are processed through the [`Tera`] engine as well.
+pub fn references(files: &[RepoFile], names: &[&str]) -> Result<bool> {
+    for file in files {
+        if settings::references(file.name.as_str(), names) {
+            return Ok(true);
+        }
+
+        let found = match &file.kind {
+            FileKind::Template(_) => {
+                let content = fs::read_to_string(&file.path)
+                    .with_context(|| format!("failed to read template `{}`", file.name))?;
+                settings::references(&content, names)
+            }
+            FileKind::Symlink(link) => settings::references(link.as_str(), names),
+            FileKind::Binary | FileKind::Directory => false,
+        };
+
+        if found {
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+/// Filter out the collected files from [`collect_files`] with the given ignore rules, and the
+/// conditions from the front matter of each template. Afterwards, the remaining templates are
+/// renamed as defined in their front matter.
+///
+/// Files matching any of the `foreach` rules are repeated for each element of the rule's list, and
+/// their paths rendered with the element. If multiple rules match the same file, the last one wins.
 pub fn filter_ignored(
     files: Vec<RepoFile>,
     context: &TeraContext,
     ignore: Vec<IgnorePattern>,
+    foreach: &[ForeachRule],
 ) -> Result<Vec<RepoFile>> {
     let mut set = GlobSetBuilder::new();
 
     for rule in ignore {
         if let Some(condition) = &rule.condition {
-            let result = Tera::one_off(condition, context, false)
-                .context("failed to execute condition template")?;
-            let active = result.trim().parse::<bool>().with_context(|| {
-                format!("condition did not evaluate to a boolean, but `{result}`")
-            })?;
-
-            if !active {
+            if !evaluate_condition(condition, context)? {
                 continue;
             }
         }
@@ -121,47 +418,713 @@ pub fn filter_ignored(
     }
 
     let filter = set.build().context("failed to build the glob set")?;
+    let foreach = foreach
+        .iter()
+        .map(|rule| Ok((build_glob_set(&rule.paths)?, rule)))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut outputs = HashMap::new();
+    let mut remaining = Vec::with_capacity(files.len());
+
+    for file in files {
+        if filter.is_match(&file.name) {
+            continue;
+        }
+
+        let copies = match foreach
+            .iter()
+            .rev()
+            .find(|(glob, _)| glob.is_match(&file.name))
+        {
+            Some((_, rule)) => foreach_items(context, &rule.items)?
+                .into_iter()
+                .enumerate()
+                .map(|item| RepoFile {
+                    item: Some(item),
+                    ..file.clone()
+                })
+                .collect(),
+            None => vec![file],
+        };
+
+        for file in copies {
+            let Some(file) = prepare_output(file, context)? else {
+                continue;
+            };
+
+            if let Some(other) = outputs.insert(file.output.clone(), file.name.clone()) {
+                if other == file.name {
+                    bail!("`{}` is generated twice as `{}`", file.name, file.output);
+                }
+
+                bail!(
+                    "`{}` and `{other}` are both generated as `{}`",
+                    file.name,
+                    file.output
+                );
+            }
+
+            remaining.push(file);
+        }
+    }
+
+    Ok(remaining)
+}
 
-    Ok(files
-        .into_iter()
-        .filter(|file| !filter.is_match(&file.name))
-        .collect())
+/// Get the list of elements, that files of a [`ForeachRule`] are generated for. A missing value,
+/// like from a skipped setting, is treated as empty list.
+fn foreach_items(context: &TeraContext, name: &str) -> Result<Vec<tera::Value>> {
+    match context.get(name) {
+        Some(tera::Value::Array(items)) => Ok(items.clone()),
+        Some(_) => bail!("the value of `{name}` is not a list, and can't be used in `foreach`"),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Evaluate the front matter condition of a file, and determine its path in the generated project.
+/// Returns `None`, if the file is excluded by its condition.
+fn prepare_output(mut file: RepoFile, context: &TeraContext) -> Result<Option<RepoFile>> {
+    let context = file.context(context);
+
+    if let FileKind::Template(front) = &file.kind {
+        if let Some(condition) = &front.condition {
+            if !evaluate_condition(condition, &context)
+                .with_context(|| format!("invalid condition in `{}`", file.name))?
+            {
+                return Ok(None);
+            }
+        }
+
+        if let Some(rename) = &front.rename {
+            let rename = Tera::one_off(rename, &context, false)
+                .with_context(|| format!("failed to render new name for `{}`", file.name))?;
+
+            file.output = resolve_link(&file.name, Utf8Path::new(rename.trim()))
+                .filter(|output| !output.as_str().is_empty())
+                .with_context(|| {
+                    format!(
+                        "`{}` is renamed outside of the project to `{rename}`",
+                        file.name
+                    )
+                })?;
+        }
+    }
+
+    if file.item.is_some() {
+        let output = Tera::one_off(file.output.as_str(), &context, false)
+            .with_context(|| format!("failed to render the path of `{}`", file.name))?;
+
+        file.output = resolve_link(Utf8Path::new(""), Utf8Path::new(&output))
+            .filter(|output| !output.as_str().is_empty())
+            .with_context(|| {
+                format!(
+                    "`{}` is generated outside of the project as `{output}`",
+                    file.name
+                )
+            })?;
+    }
+
+    Ok(Some(file))
+}
+
+/// Execute a condition template, that must evaluate to either `true` or `false`.
+fn evaluate_condition(condition: &str, context: &TeraContext) -> Result<bool> {
+    let result =
+        Tera::one_off(condition, context, false).context("failed to execute condition template")?;
+
+    result
+        .trim()
+        .parse::<bool>()
+        .with_context(|| format!("condition did not evaluate to a boolean, but `{result}`"))
 }
 
 /// Render all the given files to the target path.
 ///
 /// - If the a file is a template, it is processed through the [`Tera`] engine.
 /// - Otherwise, it's copied as-is, without any further processing.
-pub fn render(files: &[RepoFile], context: &TeraContext, target: &Utf8Path) -> Result<()> {
+///
+/// In both cases, the permissions of the original file are kept. Empty directories are created as
+/// well, so they aren't lost in the generated project.
+pub fn render(
+    files: &[RepoFile],
+    context: &TeraContext,
+    target: &Utf8Path,
+    skip_empty: bool,
+) -> Result<()> {
     let tera = {
         let mut tera = Tera::default();
-        tera.add_template_files(
-            files
-                .iter()
-                .filter_map(|f| f.template.then_some((&f.path, Some(&f.name)))),
-        )
-        .context("failed loading templates")?;
+        let templates = files
+            .iter()
+            .filter_map(|f| match &f.kind {
+                FileKind::Template(front) => Some((f, front)),
+                _ => None,
+            })
+            .map(|(f, front)| Ok((f.name.as_str(), load_template(f, front)?)))
+            .collect::<Result<Vec<_>>>()?;
+
+        tera.add_raw_templates(templates)
+            .context("failed loading templates")?;
         tera
     };
 
     fs::create_dir_all(target)?;
 
     for file in files {
-        if let Some(parent) = file.name.parent() {
+        let path = target.join(&file.output);
+        let rendered = match &file.kind {
+            FileKind::Template(_) => Some(
+                tera.render(file.name.as_str(), &file.context(context))
+                    .with_context(|| format!("failed to render template for `{}`", file.name))?,
+            ),
+            _ => None,
+        };
+
+        if skip_empty
+            && rendered
+                .as_deref()
+                .map_or(false, |output| output.trim().is_empty())
+        {
+            continue;
+        }
+
+        if let Some(parent) = file.output.parent() {
             fs::create_dir_all(target.join(parent))
                 .with_context(|| format!("failed to directories for `{parent}`"))?;
         }
 
-        if file.template {
-            let mut out = BufWriter::new(File::create(target.join(&file.name))?);
-            tera.render_to(file.name.as_str(), context, &mut out)
-                .with_context(|| format!("failed to render template for `{}`", file.name))?;
-            out.flush().context("failed to flush output file")?;
-        } else {
-            fs::copy(&file.path, target.join(&file.name))
-                .with_context(|| format!("faile to copy file `{}`", file.name))?;
+        match &file.kind {
+            FileKind::Template(_) => {
+                fs::write(&path, rendered.unwrap_or_default())
+                    .with_context(|| format!("failed to write file `{}`", file.output))?;
+
+                let permissions = fs::metadata(&file.path)
+                    .with_context(|| format!("failed to read metadata of `{}`", file.name))?
+                    .permissions();
+                fs::set_permissions(&path, permissions)
+                    .with_context(|| format!("failed to set permissions of `{}`", file.name))?;
+            }
+            FileKind::Binary => {
+                fs::copy(&file.path, &path)
+                    .with_context(|| format!("faile to copy file `{}`", file.name))?;
+            }
+            FileKind::Directory => {
+                fs::create_dir_all(&path)
+                    .with_context(|| format!("failed to create directory `{}`", file.name))?;
+            }
+            FileKind::Symlink(link) => {
+                let link = Tera::one_off(link.as_str(), &file.context(context), false)
+                    .with_context(|| {
+                        format!("failed to render symlink target for `{}`", file.name)
+                    })?;
+                let link = Utf8PathBuf::from(link);
+
+                // Checked against the generated path, as foreach rules can change its depth.
+                ensure!(
+                    resolve_link(&file.output, &link).is_some(),
+                    "symlink `{}` points outside of the target directory",
+                    file.output
+                );
+
+                create_symlink(&file.path, &link, &path)
+                    .with_context(|| format!("failed to create symlink `{}`", file.name))?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Load the content of a template file, without its front matter and translated to the regular Tera
+/// delimiters.
+fn load_template(file: &RepoFile, front: &FrontMatter) -> Result<String> {
+    let content = fs::read_to_string(&file.path)
+        .with_context(|| format!("failed to read template `{}`", file.name))?;
+    let (_, content) = split_front_matter(&content)
+        .with_context(|| format!("invalid front matter in `{}`", file.name))?;
+
+    match &front.delimiters {
+        Some(delimiters) => convert_delimiters(content, delimiters)
+            .with_context(|| format!("invalid delimiters in `{}`", file.name)),
+        None => Ok(content.to_owned()),
+    }
+}
+
+/// Create a symlink at `path`, that points to `link`. The `source` is the original symlink, which
+/// is used to determine whether it points to a directory, as Windows distinguishes between both.
+#[cfg(unix)]
+fn create_symlink(_source: &Utf8Path, link: &Utf8Path, path: &Utf8Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(link, path)
+}
+
+/// Create a symlink at `path`, that points to `link`. The `source` is the original symlink, which
+/// is used to determine whether it points to a directory, as Windows distinguishes between both.
+#[cfg(windows)]
+fn create_symlink(source: &Utf8Path, link: &Utf8Path, path: &Utf8Path) -> io::Result<()> {
+    if source.is_dir() {
+        std::os::windows::fs::symlink_dir(link, path)
+    } else {
+        std::os::windows::fs::symlink_file(link, path)
+    }
+}
+
+/// Apply the file modes from the `[permissions]` of the template settings to all matching files in
+/// the target directory. Patterns are matched against the output path of each file, so renamed
+/// and repeated files are matched by their final name. If multiple patterns match the same file,
+/// the last one wins.
+///
+/// File modes only exist on Unix systems, so this doesn't change any files on other platforms.
+pub fn apply_permissions(
+    files: &[RepoFile],
+    target: &Utf8Path,
+    permissions: &IndexMap<String, FileMode>,
+) -> Result<()> {
+    let matchers = permissions
+        .iter()
+        .map(|(pattern, mode)| {
+            let glob = GlobBuilder::new(pattern)
+                .literal_separator(true)
+                .build()
+                .with_context(|| format!("invalid glob pattern `{pattern}`"))?;
+            Ok((glob.compile_matcher(), *mode))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    for file in files
+        .iter()
+        .filter(|file| !matches!(file.kind, FileKind::Symlink(_)))
+    {
+        let path = target.join(&file.output);
+
+        // Skipped files, that rendered to empty content, don't exist in the target.
+        if !path.exists() {
+            continue;
+        }
+
+        if let Some((_, mode)) = matchers
+            .iter()
+            .rev()
+            .find(|(glob, _)| glob.is_match(&file.output))
+        {
+            set_mode(&path, *mode)
+                .with_context(|| format!("failed to set permissions of `{}`", file.output))?;
         }
     }
 
     Ok(())
 }
+
+#[cfg(unix)]
+fn set_mode(path: &Utf8Path, mode: FileMode) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    fs::set_permissions(path, fs::Permissions::from_mode(mode.0))
+}
+
+#[cfg(not(unix))]
+#[allow(clippy::unnecessary_wraps)]
+fn set_mode(_path: &Utf8Path, _mode: FileMode) -> io::Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn settings(toml: &str) -> RepoSettings {
+        basic_toml::from_str(toml).unwrap()
+    }
+
+    /// Create a temporary directory with an empty `source` folder for the template, and the path
+    /// of a `target` folder for the generated files. The directory is deleted once the returned
+    /// guard is dropped.
+    fn fixture() -> (TempDir, Utf8PathBuf, Utf8PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let root = Utf8Path::from_path(dir.path()).unwrap();
+        let (source, target) = (root.join("source"), root.join("target"));
+        fs::create_dir(&source).unwrap();
+
+        (dir, source, target)
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let (_dir, source, target) = fixture();
+
+        fs::create_dir_all(source.join("bin")).unwrap();
+        fs::write(source.join("release.sh"), "echo {{ name }}").unwrap();
+        fs::write(source.join("bin/run"), "run").unwrap();
+        fs::write(
+            source.join("bin/tool"),
+            "{# hatch: rename = \"{{ name }}\" #}\ntool",
+        )
+        .unwrap();
+        fs::set_permissions(source.join("release.sh"), fs::Permissions::from_mode(0o755)).unwrap();
+
+        let mut ctx = TeraContext::new();
+        ctx.insert("name", "hatch");
+
+        let files = collect_files(&source, &settings("")).unwrap();
+        let files = filter_ignored(files, &ctx, Vec::new(), &[]).unwrap();
+
+        render(&files, &ctx, &target, false).unwrap();
+        apply_permissions(
+            &files,
+            &target,
+            &[
+                ("bin/*".to_owned(), FileMode(0o700)),
+                ("bin/hatch".to_owned(), FileMode(0o750)),
+                ("bin/tool".to_owned(), FileMode(0o600)),
+            ]
+            .into_iter()
+            .collect(),
+        )
+        .unwrap();
+
+        let mode = |name: &str| {
+            fs::metadata(target.join(name))
+                .unwrap()
+                .permissions()
+                .mode()
+                & 0o7777
+        };
+        assert_eq!(0o755, mode("release.sh"));
+        assert_eq!(0o700, mode("bin/run"));
+        assert_eq!(0o750, mode("bin/hatch"));
+    }
+
+    #[test]
+    fn file_kinds() {
+        let (_dir, source, _) = fixture();
+
+        fs::create_dir_all(source.join(".github/workflows")).unwrap();
+        fs::write(source.join("logo.png"), "{{ not a template }}").unwrap();
+        fs::write(source.join("blob"), b"\x00\x01\x02").unwrap();
+        fs::write(source.join("latin1.txt"), b"caf\xe9").unwrap();
+        fs::write(source.join("README.md"), "# {{ name }}").unwrap();
+        fs::write(source.join(".github/workflows/ci.yml"), "${{ matrix.os }}").unwrap();
+        fs::write(source.join(".github/workflows/release.yml"), "{{ name }}").unwrap();
+
+        let files = collect_files(
+            &source,
+            &settings(
+                r#"
+                raw = ["**/*.yml"]
+                template = [".github/workflows/release.yml"]
+                "#,
+            ),
+        )
+        .unwrap();
+
+        let mut kinds = files
+            .iter()
+            .map(|f| (f.name.as_str(), matches!(f.kind, FileKind::Template(_))))
+            .collect::<Vec<_>>();
+        kinds.sort_unstable();
+
+        assert_eq!(
+            vec![
+                (".github/workflows/ci.yml", false),
+                (".github/workflows/release.yml", true),
+                ("README.md", true),
+                ("blob", false),
+                ("latin1.txt", false),
+                ("logo.png", false),
+            ],
+            kinds
+        );
+    }
+
+    #[test]
+    fn delimiters() {
+        let (_dir, source, target) = fixture();
+
+        fs::create_dir_all(source.join(".github/workflows")).unwrap();
+        fs::write(
+            source.join(".github/workflows/ci.yml"),
+            "name: [[ name ]]\n[% if ci %]os: ${{ matrix.os }}[% endif %]\n",
+        )
+        .unwrap();
+        fs::write(
+            source.join("index.html"),
+            "{#- hatch: delimiters = { variable = [\"<<\", \">>\"] } -#}\n\n<h1><< name >></h1>{{ \
+             title }}{% if true %}!{% endif %}",
+        )
+        .unwrap();
+
+        let files = collect_files(
+            &source,
+            &settings(
+                r#"
+                [[delimiters]]
+                paths = [".github/**"]
+                variable = ["[[", "]]"]
+                block = ["[%", "%]"]
+                "#,
+            ),
+        )
+        .unwrap();
+
+        let mut ctx = TeraContext::new();
+        ctx.insert("name", "hatch");
+        ctx.insert("ci", &true);
+
+        render(&files, &ctx, &target, false).unwrap();
+
+        let read = |name: &str| fs::read_to_string(target.join(name)).unwrap();
+        assert_eq!(
+            "name: hatch\nos: ${{ matrix.os }}\n",
+            read(".github/workflows/ci.yml")
+        );
+        assert_eq!("<h1>hatch</h1>{{ title }}!", read("index.html"));
+
+        assert_eq!(
+            Some("start delimiters must be distinguishable from each other"),
+            Delimiters {
+                variable: Some(("[".to_owned(), "]".to_owned())),
+                block: Some(("[%".to_owned(), "%]".to_owned())),
+                comment: None,
+            }
+            .validate()
+        );
+
+        let variable = |start: &str| Delimiters {
+            variable: Some((start.to_owned(), "}}".to_owned())),
+            ..Delimiters::default()
+        };
+        let error = Some(
+            "start delimiters must be distinguishable from the regular delimiters of kinds that \
+             aren't replaced",
+        );
+        assert_eq!(None, variable("{{").validate());
+        assert_eq!(None, variable("${").validate());
+        assert_eq!(error, variable("{%").validate());
+        assert_eq!(error, variable("{").validate());
+        assert_eq!(error, variable("{#-").validate());
+    }
+
+    #[test]
+    fn front_matter() {
+        let (_dir, source, target) = fixture();
+
+        fs::create_dir_all(source.join("src")).unwrap();
+        fs::write(
+            source.join("src/main.rs"),
+            "{#- hatch: condition = \"{{ crate_bin }}\" -#}\nfn main() {}",
+        )
+        .unwrap();
+        fs::write(
+            source.join("src/lib.rs"),
+            "{# hatch:\ncondition = \"{{ not crate_bin }}\"\n#}\npub fn run() {}",
+        )
+        .unwrap();
+        fs::write(
+            source.join("src/module.rs"),
+            "{# hatch: rename = \"{{ name }}.rs\" #}\n// {{ name }}",
+        )
+        .unwrap();
+        fs::write(source.join("build.rs"), "{% if crate_bin %}\n{% endif %}\n").unwrap();
+
+        let mut ctx = TeraContext::new();
+        ctx.insert("crate_bin", &true);
+        ctx.insert("name", "hatch");
+
+        let files = collect_files(&source, &settings("")).unwrap();
+        let files = filter_ignored(files, &ctx, Vec::new(), &[]).unwrap();
+
+        render(&files, &ctx, &target, true).unwrap();
+
+        let read = |name: &str| fs::read_to_string(target.join(name)).ok();
+        assert_eq!(Some("fn main() {}".to_owned()), read("src/main.rs"));
+        assert_eq!(None, read("src/lib.rs"));
+        assert_eq!(Some("// hatch".to_owned()), read("src/hatch.rs"));
+        assert_eq!(None, read("src/module.rs"));
+        assert_eq!(None, read("build.rs"));
+
+        ctx.insert("name", "main");
+        let files = collect_files(&source, &settings("")).unwrap();
+        assert!(filter_ignored(files, &ctx, Vec::new(), &[]).is_err());
+
+        ctx.insert("name", "../../outside");
+        let files = collect_files(&source, &settings("")).unwrap();
+        assert!(filter_ignored(files, &ctx, Vec::new(), &[]).is_err());
+    }
+
+    #[test]
+    fn foreach() {
+        let (_dir, source, target) = fixture();
+
+        fs::create_dir_all(source.join("crates/{{ item }}")).unwrap();
+        fs::write(
+            source.join("crates/{{ item }}/Cargo.toml"),
+            "name = \"{{ item }}\"\nindex = {{ index }}",
+        )
+        .unwrap();
+        fs::write(
+            source.join("Cargo.toml"),
+            "{{ services | join(sep=\",\") }}",
+        )
+        .unwrap();
+
+        let rules = [ForeachRule {
+            paths: vec!["crates/*/**".to_owned()],
+            items: "services".to_owned(),
+        }];
+        let expand = |services: &[&str]| {
+            let mut ctx = TeraContext::new();
+            ctx.insert("services", services);
+
+            let files = collect_files(&source, &settings("")).unwrap();
+            filter_ignored(files, &ctx, Vec::new(), &rules).map(|files| (files, ctx))
+        };
+
+        let (files, ctx) = expand(&["api", "worker"]).unwrap();
+        render(&files, &ctx, &target, false).unwrap();
+
+        let read = |name: &str| fs::read_to_string(target.join(name)).unwrap();
+        assert_eq!("api,worker", read("Cargo.toml"));
+        assert_eq!("name = \"api\"\nindex = 0", read("crates/api/Cargo.toml"));
+        assert_eq!(
+            "name = \"worker\"\nindex = 1",
+            read("crates/worker/Cargo.toml")
+        );
+
+        assert_eq!(1, expand(&[]).unwrap().0.len());
+        assert!(expand(&["api", "api"]).is_err());
+        assert!(expand(&["../.."]).is_err());
+
+        let repo = settings(
+            r#"
+            [services]
+            type = "multi_list"
+            description = "services"
+            values = ["api", "worker", "cron", "gateway"]
+            "#,
+        );
+        let defaults = basic_toml::from_str::<HashMap<_, settings::DefaultSetting>>(
+            r#"services = { value = ["gateway", "cron", "api"], skip_prompt = true }"#,
+        )
+        .unwrap();
+
+        let mut ctx = TeraContext::new();
+        settings::fill_context(&mut ctx, &repo, defaults).unwrap();
+        let files = collect_files(&source, &repo).unwrap();
+        let files = filter_ignored(files, &ctx, Vec::new(), &rules).unwrap();
+
+        let items = files
+            .iter()
+            .filter_map(|file| file.item.as_ref())
+            .map(|(index, item)| (*index, item.as_str().unwrap()))
+            .collect::<Vec<_>>();
+        assert_eq!(vec![(0, "api"), (1, "cron"), (2, "gateway")], items);
+    }
+
+    #[test]
+    fn empty_directories() {
+        let (_dir, source, target) = fixture();
+
+        fs::create_dir_all(source.join("assets/icons")).unwrap();
+        fs::create_dir_all(source.join("migrations")).unwrap();
+        fs::create_dir_all(source.join("src")).unwrap();
+        fs::write(source.join("src/main.rs"), "fn main() {}").unwrap();
+
+        let files = collect_files(&source, &settings("")).unwrap();
+        let mut names = files.iter().map(|f| f.name.as_str()).collect::<Vec<_>>();
+        names.sort_unstable();
+        assert_eq!(vec!["assets/icons", "migrations", "src/main.rs"], names);
+
+        let files = filter_ignored(
+            files,
+            &TeraContext::new(),
+            vec![IgnorePattern {
+                paths: vec!["migrations".into()],
+                condition: None,
+            }],
+            &[],
+        )
+        .unwrap();
+
+        render(&files, &TeraContext::new(), &target, false).unwrap();
+
+        assert!(target.join("assets/icons").is_dir());
+        assert!(!target.join("migrations").exists());
+        assert!(target.join("src/main.rs").is_file());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn symlinks() {
+        let (_dir, source, target) = fixture();
+
+        fs::create_dir_all(source.join("docs")).unwrap();
+        fs::write(source.join("hatch.md"), "# {{ name }}").unwrap();
+        std::os::unix::fs::symlink("../{{ name }}.md", source.join("docs/README.md")).unwrap();
+        std::os::unix::fs::symlink("docs", source.join("documentation")).unwrap();
+
+        let files = collect_files(&source, &settings("")).unwrap();
+        let mut ctx = TeraContext::new();
+        ctx.insert("name", "hatch");
+
+        render(&files, &ctx, &target, false).unwrap();
+
+        assert_eq!(
+            Utf8Path::new("../hatch.md"),
+            target.join("docs/README.md").read_link_utf8().unwrap()
+        );
+        assert_eq!(
+            "# hatch",
+            fs::read_to_string(target.join("documentation/README.md")).unwrap()
+        );
+
+        std::os::unix::fs::symlink("{{ git_name }}.md", source.join("docs/author")).unwrap();
+        let files = collect_files(&source, &settings("")).unwrap();
+        assert!(references(&files, &["git_name"]).unwrap());
+        assert!(!references(&files, &["git_email"]).unwrap());
+
+        std::os::unix::fs::symlink("../../outside", source.join("docs/escape")).unwrap();
+        assert!(collect_files(&source, &settings("")).is_err());
+
+        let (_dir, source, target) = fixture();
+        fs::create_dir_all(source.join("crates/{{ item }}")).unwrap();
+        fs::write(source.join("README.md"), "# workspace").unwrap();
+        std::os::unix::fs::symlink(
+            "../../README.md",
+            source.join("crates/{{ item }}/README.md"),
+        )
+        .unwrap();
+
+        let rules = [ForeachRule {
+            paths: vec!["crates/**".to_owned()],
+            items: "services".to_owned(),
+        }];
+        let render_items = |services: &[&str]| {
+            let mut ctx = TeraContext::new();
+            ctx.insert("services", services);
+
+            let files = collect_files(&source, &settings("")).unwrap();
+            let files = filter_ignored(files, &ctx, Vec::new(), &rules).unwrap();
+            render(&files, &ctx, &target, false)
+        };
+
+        render_items(&["api"]).unwrap();
+        assert_eq!(
+            Utf8Path::new("../../README.md"),
+            target
+                .join("crates/api/README.md")
+                .read_link_utf8()
+                .unwrap()
+        );
+        assert!(render_items(&[""]).is_err());
+
+        let resolve =
+            |name: &str, link: &str| resolve_link(Utf8Path::new(name), Utf8Path::new(link));
+        assert_eq!(Some("a/c".into()), resolve("a/b", "./c"));
+        assert_eq!(Some("c".into()), resolve("a/b", "../c"));
+        assert_eq!(None, resolve("a/b", "../../c"));
+        assert_eq!(None, resolve("a", "/etc/passwd"));
+    }
+}